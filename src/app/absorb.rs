@@ -0,0 +1,263 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+use crate::app::restack;
+use crate::data::restack::RestackState;
+use crate::git::diff::{FileDiff, Hunk};
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// A branch that receives some of the staged hunks as a fixup commit.
+struct Target {
+    branch: String,
+    /// The commit the first hunk fixes up, named in the fixup commit's message.
+    commit: String,
+    /// Indexes into the staged files and their hunks.
+    hunks: Vec<(usize, usize)>,
+    tree: String,
+}
+
+pub fn absorb(yes: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let mut state = data::SolMetadata::load()?;
+    let current_branch = state.checked_out_branch()?;
+    let stack = state.get_current_stack()?;
+
+    if !git::commit::has_staged_changes()? {
+        return Err(anyhow!("No staged changes to absorb."));
+    }
+    if git::commit::has_unstaged_changes()? {
+        return Err(anyhow!(
+            "You have unstaged changes. Stage or stash them before absorbing."
+        ));
+    }
+
+    // Every commit that belongs to the current branch or a branch below it.
+    let ancestry = stack.ancestry(&current_branch)?;
+    let mut owners = HashMap::new();
+    for branch in &ancestry {
+        for commit in git::commit::list(stack.parent_of(branch), &branch.name)? {
+            owners.insert(commit.hash, branch.name.clone());
+        }
+    }
+
+    let files = git::diff::staged()?;
+    let mut targets: Vec<Target> = Vec::new();
+    for (file_index, file) in files.iter().enumerate() {
+        if !file.is_line_edit() {
+            continue;
+        }
+        for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+            let Some((branch, commit)) = owner(&owners, file, hunk) else {
+                continue;
+            };
+            match targets.iter_mut().find(|target| target.branch == branch) {
+                Some(target) => target.hunks.push((file_index, hunk_index)),
+                None => targets.push(Target {
+                    branch,
+                    commit,
+                    hunks: vec![(file_index, hunk_index)],
+                    tree: String::new(),
+                }),
+            }
+        }
+    }
+
+    // Build each fixup on top of its branch now, so hunks that do not apply there stay staged.
+    let mut failed = Vec::new();
+    for target in targets.iter_mut() {
+        match build_tree(&files, target) {
+            Ok(tree) => target.tree = tree,
+            Err(err) => failed.push((target.branch.clone(), err)),
+        }
+    }
+    targets.retain(|target| !target.tree.is_empty());
+    targets.sort_by_key(|target| ancestry.iter().position(|branch| branch.name == target.branch));
+
+    print_plan(&files, &targets, &failed);
+    if targets.is_empty() {
+        return Err(anyhow!("None of the staged hunks could be matched to a branch in the stack."));
+    }
+    if !yes
+        && !inquire::Confirm::new("Create these fixup commits and restack?")
+            .with_default(true)
+            .prompt()?
+    {
+        println!("Nothing was changed.");
+        return Ok(());
+    }
+
+    let abort_patch = git::diff::staged_patch(3)?;
+    let finish_patch = leftover_patch(&files, &targets);
+    let lowest = targets[0].branch.clone();
+    let pending = stack
+        .descendants(&lowest)
+        .iter()
+        .map(|branch| branch.name.clone())
+        .collect();
+    let mut pending = RestackState::new("absorb", &current_branch, pending);
+
+    for target in &targets {
+        let old_tip = git::commit::resolve(&target.branch)?;
+        let message = format!("fixup! {}", git::commit::subject(&target.commit)?);
+        let new_tip = git::commit::commit_tree(&target.tree, &old_tip, &message)?;
+        git::branch::update_ref(&target.branch, &new_tip)?;
+
+        let stack = state
+            .find_stack_mut(&target.branch)
+            .ok_or_else(|| anyhow!("Branch '{}' is not tracked.", target.branch))?;
+        stack.get_branch_mut(&target.branch)?.set_commit_hash(new_tip);
+        pending.old_tips.insert(target.branch.clone(), old_tip);
+
+        println!(
+            "Absorbed {} hunk(s) into '{}'",
+            target.hunks.len(),
+            ui::paint(Role::Branch, &target.branch)
+        );
+    }
+
+    // The absorbed hunks reach this branch through the restack; the rest are staged again after.
+    git::branch::discard_changes()?;
+    pending.finish_patch = (!finish_patch.is_empty()).then_some(finish_patch);
+    pending.abort_patch = Some(abort_patch);
+    restack::run(pending, &mut state)
+}
+
+/// Finds the branch whose commits last touched the lines a hunk changes. Insertions look at
+/// the lines around them. Hunks touching several branches, or the base, have no owner.
+fn owner(owners: &HashMap<String, String>, file: &FileDiff, hunk: &Hunk) -> Option<(String, String)> {
+    let commits = if hunk.old_count > 0 {
+        git::diff::blame("HEAD", &file.path, hunk.old_start, hunk.old_end()).ok()?
+    } else {
+        [hunk.old_start, hunk.old_start + 1]
+            .into_iter()
+            .filter(|line| *line > 0)
+            .filter_map(|line| git::diff::blame("HEAD", &file.path, line, line).ok())
+            .flatten()
+            .collect()
+    };
+
+    let mut branches = commits.iter().map(|commit| owners.get(commit));
+    let branch = branches.next()??;
+    if branches.any(|other| other != Some(branch)) {
+        return None;
+    }
+    Some((branch.clone(), commits[0].clone()))
+}
+
+/// Applies a target's hunks to its branch tip, moving them past the changes made above it.
+fn build_tree(files: &[FileDiff], target: &Target) -> Result<String> {
+    let mut patch = String::new();
+    for (file_index, file) in files.iter().enumerate() {
+        let hunks: Vec<&Hunk> = target
+            .hunks
+            .iter()
+            .filter(|(index, _)| *index == file_index)
+            .map(|(_, hunk_index)| &file.hunks[*hunk_index])
+            .collect();
+        if hunks.is_empty() {
+            continue;
+        }
+
+        let above = git::diff::between(&target.branch, "HEAD", &file.path)?;
+        let shifted: Vec<(&Hunk, i64)> = hunks
+            .into_iter()
+            .map(|hunk| (hunk, offset_below(&above, hunk)))
+            .collect();
+        patch.push_str(&file.render(&shifted));
+    }
+
+    git::diff::apply_to_tree(&target.branch, &patch)
+}
+
+/// How far a hunk's lines moved because of `changes` made before them, in the other direction.
+fn offset_below(changes: &[Hunk], hunk: &Hunk) -> i64 {
+    let before = if hunk.old_count == 0 { hunk.old_start } else { hunk.old_start - 1 };
+    changes
+        .iter()
+        .filter(|change| change.new_count == 0 && change.new_start <= before
+            || change.new_count > 0 && change.new_start + change.new_count - 1 <= before)
+        .map(|change| change.old_count as i64 - change.new_count as i64)
+        .sum()
+}
+
+/// The staged changes that were not absorbed, with line numbers moved past the absorbed hunks.
+fn leftover_patch(files: &[FileDiff], targets: &[Target]) -> String {
+    let absorbed = |file_index: usize, hunk_index: usize| {
+        targets
+            .iter()
+            .any(|target| target.hunks.contains(&(file_index, hunk_index)))
+    };
+
+    let mut patch = String::new();
+    for (file_index, file) in files.iter().enumerate() {
+        let mut offset = 0;
+        let mut leftover = Vec::new();
+        for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+            if absorbed(file_index, hunk_index) {
+                offset += hunk.delta();
+            } else {
+                leftover.push((hunk, offset));
+            }
+        }
+
+        if leftover.len() == file.hunks.len() {
+            patch.push_str(&file.render_all());
+        } else if !leftover.is_empty() {
+            patch.push_str(&file.render(&leftover));
+        }
+    }
+    patch
+}
+
+fn print_plan(files: &[FileDiff], targets: &[Target], failed: &[(String, anyhow::Error)]) {
+    let location = |(file_index, hunk_index): (usize, usize)| {
+        let file = &files[file_index];
+        let hunk = &file.hunks[hunk_index];
+        format!("{}:{}", file.path, hunk.old_start.max(1))
+    };
+
+    for target in targets {
+        println!(
+            "{} ({})",
+            ui::paint(Role::Branch, &target.branch),
+            ui::paint(Role::Hash, &target.commit[..7.min(target.commit.len())])
+        );
+        for hunk in &target.hunks {
+            println!("  {}", location(*hunk));
+        }
+    }
+
+    let staying: Vec<String> = files
+        .iter()
+        .enumerate()
+        .flat_map(|(file_index, file)| {
+            let hunks = if file.hunks.is_empty() { vec![usize::MAX] } else { (0..file.hunks.len()).collect() };
+            hunks.into_iter().map(move |hunk_index| (file_index, hunk_index))
+        })
+        .filter(|hunk| !targets.iter().any(|target| target.hunks.contains(hunk)))
+        .map(|(file_index, hunk_index)| match hunk_index {
+            usize::MAX => files[file_index].path.clone(),
+            _ => location((file_index, hunk_index)),
+        })
+        .collect();
+    if !staying.is_empty() {
+        println!("{}", ui::paint(Role::Muted, "Staying staged:"));
+        for hunk in staying {
+            println!("  {}", ui::paint(Role::Muted, &hunk));
+        }
+    }
+
+    for (branch, err) in failed {
+        println!(
+            "{} Could not apply the hunks for '{}' there: {}",
+            ui::paint(Role::Warning, "warning:"),
+            branch,
+            err
+        );
+    }
+}
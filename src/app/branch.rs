@@ -1,7 +1,18 @@
 use crate::{data, errors, git};
 use anyhow::{anyhow, Result};
 
-pub fn branch(name: String, from: Option<String>, verbose: bool) -> Result<()> {
+/// Where a new branch goes relative to the current branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// On top of the current branch, as a new leaf.
+    Leaf,
+    /// Between the current branch and its children.
+    Insert,
+    /// Between the current branch and its parent.
+    Below,
+}
+
+pub fn branch(name: String, from: Option<String>, placement: Placement, verbose: bool) -> Result<()> {
     if !git::repo::is_repo()? {
         return Err(errors::git::GitError::NotGitRepository.into());
     }
@@ -16,6 +27,13 @@ pub fn branch(name: String, from: Option<String>, verbose: bool) -> Result<()> {
         if current_stack.has_branch(&name) || state.has_branch(&name) {
             return Err(anyhow!("Branch already exists."));
         }
+        if git::branch::exists(&name) {
+            return Err(anyhow!(
+                "A git branch named '{}' already exists. Run `zyra track {}` to add it to the stack.",
+                name,
+                name
+            ));
+        }
 
         // Check to ensure the `from` branch exists.
         if let Some(from_branch) = &from {
@@ -37,36 +55,58 @@ pub fn branch(name: String, from: Option<String>, verbose: bool) -> Result<()> {
         git::branch::switch(&from_branch, false)?;
     }
 
-    // Getting the current commit hash.
-    let commit_hash = git::commit::get_hash()?;
-    let current_branch = git::branch::get_branch_name()?;
+    let current_branch = state.current_branch()?;
+    let current_stack_mut = state.get_current_stack_mut()?;
+    let parent_name = current_stack_mut
+        .parent_of(current_stack_mut.get_branch(&current_branch)?)
+        .to_string();
+
+    // Below the current branch the new branch starts where we forked from the parent, otherwise
+    // at our tip. Either way the branches around it are already based on that commit.
+    let start = match placement {
+        Placement::Below => git::commit::merge_base(&parent_name, &current_branch)?,
+        Placement::Leaf | Placement::Insert => git::commit::resolve(&current_branch)?,
+    };
 
     // Creating the branch.
-    let mut branch = data::StackBranch::new(name.clone(), commit_hash);
-    branch.set_parent(current_branch.clone());
+    let mut branch = data::StackBranch::new(name.clone(), start.clone());
+    branch.set_parent_hash(start.clone());
+    match placement {
+        Placement::Leaf | Placement::Insert => branch.set_parent(current_branch.clone()),
+        Placement::Below => {
+            let current = current_stack_mut.get_branch_mut(&current_branch)?;
+            branch.parent = current.parent.replace(name.clone());
+            current.set_parent_hash(start.clone());
+        }
+    }
 
-    // Add the branch to the current stack
-    let current_stack_mut = state.get_current_stack_mut()?;
-    
-    // Calculate and set the depth based on parent chain
-    let depth = current_stack_mut.calculate_branch_depth(&name);
-    branch.depth = depth;
-    
+    if placement == Placement::Insert {
+        for child in current_stack_mut.branches.iter_mut() {
+            if child.parent.as_deref() == Some(current_branch.as_str()) {
+                child.set_parent(name.clone());
+            }
+        }
+    }
+
+    // Add the branch to the current stack, then recalculate depths along the changed chain.
     current_stack_mut.add_branch(branch)?;
+    current_stack_mut.refresh();
 
     // Store the stack display before saving
     let stack_display = current_stack_mut.simple_display();
 
-    state.save()?;
+    // Create the new git branch before recording it, so a failure leaves the stack as it was.
+    git::branch::create_from(&name, &start)?;
 
-    // Create the new git branch
-    git::branch::switch(&name, true)?;
-    git::branch::set_upstream(&name)?;
+    // We end up on the new branch, even if HEAD was detached on a commit of this one.
+    state.clear_detached_head_context();
+    state.save()?;
 
     if verbose {
         println!(
             "[sol] Created new branch '{}' from parent branch '{}'.",
-            name, current_branch
+            name,
+            if placement == Placement::Below { &parent_name } else { &current_branch }
         );
     } else {
         println!("Updated stack: {}", stack_display);
@@ -0,0 +1,46 @@
+use crate::config::{Config, Scope, KEYS};
+use crate::ui::{self, Role};
+use anyhow::{anyhow, Result};
+
+pub fn get(key: &str) -> Result<()> {
+    let config = Config::load()?;
+    match config.get(key)? {
+        Some(value) => {
+            println!("{}", value);
+            Ok(())
+        }
+        None => Err(anyhow!("'{}' is not set.", key)),
+    }
+}
+
+pub fn set(key: &str, value: &str, global: bool) -> Result<()> {
+    let scope = if global { Scope::User } else { Scope::Repo };
+
+    let mut config = Config::load_scope(scope)?;
+    config.set(key, value)?;
+    let path = config.save_scope(scope)?;
+
+    println!("Set {} = {} in {}", ui::paint(Role::Branch, key), value, path.display());
+    Ok(())
+}
+
+pub fn list() -> Result<()> {
+    let user = Config::load_scope(Scope::User)?;
+    let repo = Config::load_scope(Scope::Repo)?;
+
+    for key in KEYS {
+        let (value, source) = match (repo.get(key.name)?, user.get(key.name)?) {
+            (Some(value), _) => (value, "repo"),
+            (None, Some(value)) => (value, "user"),
+            (None, None) => ("(unset)".to_string(), ""),
+        };
+
+        println!(
+            "{} = {} {}",
+            ui::paint(Role::Branch, key.name),
+            value,
+            ui::paint(Role::Muted, format!("{} # {}", source, key.description).trim())
+        );
+    }
+    Ok(())
+}
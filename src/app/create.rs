@@ -0,0 +1,159 @@
+use anyhow::{anyhow, Result};
+
+use crate::config::Config;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// Longest slug taken from a commit message for a branch name.
+const MAX_SLUG_LEN: usize = 50;
+
+pub fn create(name: Option<String>, message: &str, all: bool, verbose: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let mut state = data::SolMetadata::load()?;
+    let current_branch = state.checked_out_branch()?;
+    if state.get_current_stack().is_err() {
+        return Err(anyhow!(
+            "Current branch '{}' is not part of a stack. Run `zyra init` or `zyra track` first.",
+            current_branch
+        ));
+    }
+
+    let name = match name {
+        Some(name) => name,
+        None => unique_name(&state, &derive_name(message)?),
+    };
+    if state.has_branch(&name) || git::branch::exists(&name) {
+        return Err(anyhow!("Branch already exists."));
+    }
+
+    if all {
+        git::commit::stage_all()?;
+    }
+    if !git::commit::has_staged_changes()? {
+        return Err(anyhow!(
+            "No staged changes to commit. Stage changes first or pass --all."
+        ));
+    }
+
+    let parent_hash = git::commit::resolve(&current_branch)?;
+
+    // Create the branch and commit onto it; undo the branch if the commit fails (e.g. a hook rejects it).
+    git::branch::switch(&name, true)?;
+    let commit_hash = match git::commit::create(message) {
+        Ok(hash) => hash,
+        Err(err) => {
+            git::branch::switch(&current_branch, false)?;
+            git::branch::delete(&name, true)?;
+            return Err(err);
+        }
+    };
+
+    let mut branch = data::StackBranch::new(name.clone(), commit_hash);
+    branch.set_parent(current_branch.clone());
+    branch.set_parent_hash(parent_hash);
+
+    let stack = state
+        .find_stack_mut(&current_branch)
+        .ok_or_else(|| anyhow!("No stack found for current branch."))?;
+    stack.add_branch(branch)?;
+    stack.refresh();
+    let stack_display = stack.simple_display();
+    state.save()?;
+
+    if verbose {
+        println!(
+            "[sol] Created new branch '{}' from parent branch '{}' with commit '{}'.",
+            name,
+            current_branch,
+            message.lines().next().unwrap_or_default()
+        );
+    } else {
+        println!("Created branch '{}'", ui::paint(Role::Branch, &name));
+        println!("Updated stack: {}", stack_display);
+    }
+    Ok(())
+}
+
+/// Builds a branch name from the configured template and a commit message.
+fn derive_name(message: &str) -> Result<String> {
+    let config = Config::load()?;
+    let user = git::repo::config_value("user.name").unwrap_or_default();
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let name = render_template(config.branch_template(), message, &user, &date);
+    if name.is_empty() {
+        return Err(anyhow!(
+            "Could not derive a branch name from the commit message. Pass a name explicitly."
+        ));
+    }
+    Ok(name)
+}
+
+/// Appends a counter to `name` until it is unused.
+pub(crate) fn unique_name(state: &data::SolMetadata, name: &str) -> String {
+    let taken = |candidate: &str| state.has_branch(candidate) || git::branch::exists(candidate);
+    if !taken(name) {
+        return name.to_string();
+    }
+
+    (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap()
+}
+
+/// Fills in `{message}`, `{user}` and `{date}`, then tidies separators left by empty values.
+pub fn render_template(template: &str, message: &str, user: &str, date: &str) -> String {
+    let subject = message.lines().next().unwrap_or_default();
+    let mut message_slug = slugify(subject);
+    if message_slug.len() > MAX_SLUG_LEN {
+        message_slug.truncate(MAX_SLUG_LEN);
+        message_slug = message_slug.trim_end_matches('-').to_string();
+    }
+
+    let name = template
+        .replace("{message}", &message_slug)
+        .replace("{user}", &slugify(user))
+        .replace("{date}", date);
+
+    name.split('/')
+        .map(|part| part.trim_matches('-'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Lowercases text and replaces anything that is not alphanumeric with single dashes.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(
+            render_template("{message}", "Fix: handle empty `--base`!\n\nBody", "", ""),
+            "fix-handle-empty-base"
+        );
+        assert_eq!(
+            render_template("{user}/{date}-{message}", "Add login", "Ada Lovelace", "2025-01-02"),
+            "ada-lovelace/2025-01-02-add-login"
+        );
+        assert_eq!(render_template("{user}/{message}", "Add login", "", ""), "add-login");
+        assert_eq!(render_template("{message}", &"word ".repeat(20), "", "").len(), 49);
+    }
+}
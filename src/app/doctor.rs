@@ -0,0 +1,372 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::app::restack;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// A way in which the metadata disagrees with git or with itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// A tracked branch no longer exists in git.
+    MissingBranch { stack: String, branch: String },
+    /// A branch names a parent that is not in its stack.
+    DanglingParent { stack: String, branch: String, parent: String },
+    /// Branches whose parent links lead back to themselves, starting with the one to detach.
+    Cycle { stack: String, branches: Vec<String> },
+    /// A branch is tracked more than once.
+    Duplicate { branch: String, stack: String, also_in: String },
+    /// The saved detached-HEAD context no longer matches the checkout.
+    StaleContext { branch: String },
+    /// A branch's stored depth differs from its place in the tree.
+    DepthMismatch { stack: String, branch: String, recorded: u8, actual: u8 },
+    /// A stack's base branch no longer exists. zyra cannot fix this one.
+    MissingBase { stack: String, base: String },
+}
+
+impl Issue {
+    pub fn fixable(&self) -> bool {
+        !matches!(self, Issue::MissingBase { .. })
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::MissingBranch { stack, branch } => write!(
+                f,
+                "'{}' in stack '{}' no longer exists in git; untrack it",
+                branch, stack
+            ),
+            Issue::DanglingParent { stack, branch, parent } => write!(
+                f,
+                "'{}' in stack '{}' has parent '{}', which is not in the stack; put it on the stack's root",
+                branch, stack, parent
+            ),
+            Issue::Cycle { stack, branches } => write!(
+                f,
+                "{} in stack '{}' are their own ancestors; put '{}' on the stack's root",
+                branches.iter().map(|name| format!("'{}'", name)).collect::<Vec<_>>().join(", "),
+                stack,
+                branches[0]
+            ),
+            Issue::Duplicate { branch, stack, also_in } => write!(
+                f,
+                "'{}' is tracked in '{}' and again in '{}'; keep the first",
+                branch, stack, also_in
+            ),
+            Issue::StaleContext { branch } => write!(
+                f,
+                "the detached-HEAD context for '{}' is stale; clear it",
+                branch
+            ),
+            Issue::DepthMismatch { stack, branch, recorded, actual } => write!(
+                f,
+                "'{}' in stack '{}' has depth {} but sits at depth {}; recalculate it",
+                branch, stack, recorded, actual
+            ),
+            Issue::MissingBase { stack, base } => write!(
+                f,
+                "stack '{}' is based on '{}', which does not exist; recreate the branch or delete the stack",
+                stack, base
+            ),
+        }
+    }
+}
+
+pub fn doctor(fix: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    if fix {
+        restack::ensure_idle()?;
+    }
+
+    // As saved, since loading normally drops a stale detached-HEAD context unseen.
+    let mut state = data::SolMetadata::load_stored()?;
+    let branches = git::branch::get_branches(true)?;
+    let detached = git::branch::get_branch_name()? == "HEAD";
+
+    let issues = diagnose(&state, &branches, detached);
+    if issues.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+
+    for issue in &issues {
+        let marker = if issue.fixable() { "-" } else { "!" };
+        println!("{} {}", ui::paint(Role::Warning, marker), issue);
+    }
+
+    if !fix {
+        let fixable = issues.iter().filter(|issue| issue.fixable()).count();
+        return Err(anyhow!(
+            "Found {} problem(s); run `zyra doctor --fix` to repair {} of them.",
+            issues.len(),
+            fixable
+        ));
+    }
+
+    repair(&mut state, &branches, detached);
+    state.save()?;
+
+    let remaining = diagnose(&state, &branches, detached);
+    println!("Fixed {} problem(s).", issues.len() - remaining.len());
+    if !remaining.is_empty() {
+        return Err(anyhow!("{} problem(s) need fixing by hand.", remaining.len()));
+    }
+    Ok(())
+}
+
+/// Cross-checks the metadata against the local git branches.
+pub fn diagnose(state: &data::SolMetadata, branches: &[String], detached: bool) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let exists = |name: &str| branches.iter().any(|branch| branch == name);
+
+    let mut seen: Vec<(&str, &str)> = Vec::new();
+    for stack in &state.stacks {
+        if !exists(stack.base_branch()) {
+            issues.push(Issue::MissingBase {
+                stack: stack.name.clone(),
+                base: stack.base_branch().to_string(),
+            });
+        }
+
+        // Depths cannot be worked out while parent links go round in circles.
+        let cycles = cycles(stack);
+        for branches in &cycles {
+            issues.push(Issue::Cycle {
+                stack: stack.name.clone(),
+                branches: branches.clone(),
+            });
+        }
+
+        for branch in &stack.branches {
+            if let Some((_, first)) = seen.iter().find(|(name, _)| *name == branch.name) {
+                issues.push(Issue::Duplicate {
+                    branch: branch.name.clone(),
+                    stack: first.to_string(),
+                    also_in: stack.name.clone(),
+                });
+                continue;
+            }
+            seen.push((&branch.name, &stack.name));
+
+            if !exists(&branch.name) {
+                issues.push(Issue::MissingBranch {
+                    stack: stack.name.clone(),
+                    branch: branch.name.clone(),
+                });
+            }
+            if let Some(parent) = branch.parent.as_deref().filter(|parent| !stack.has_branch(parent)) {
+                issues.push(Issue::DanglingParent {
+                    stack: stack.name.clone(),
+                    branch: branch.name.clone(),
+                    parent: parent.to_string(),
+                });
+            }
+            if cycles.is_empty() {
+                let actual = stack.calculate_branch_depth(&branch.name);
+                if branch.depth != actual {
+                    issues.push(Issue::DepthMismatch {
+                        stack: stack.name.clone(),
+                        branch: branch.name.clone(),
+                        recorded: branch.depth,
+                        actual,
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(context) = state.get_detached_head_context() {
+        let tracked = state
+            .stacks
+            .iter()
+            .any(|stack| stack.name == context.stack_name && stack.has_branch(&context.branch_name));
+        if !detached || !tracked || !exists(&context.branch_name) {
+            issues.push(Issue::StaleContext {
+                branch: context.branch_name.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Applies every fix [`diagnose`] can suggest.
+pub fn repair(state: &mut data::SolMetadata, branches: &[String], detached: bool) {
+    let exists = |name: &str| branches.iter().any(|branch| branch == name);
+
+    // Keep the first record of each branch.
+    let mut seen = HashSet::new();
+    for stack in state.stacks.iter_mut() {
+        stack.branches.retain(|branch| seen.insert(branch.name.clone()));
+    }
+
+    for stack in state.stacks.iter_mut() {
+        // Cycles go first: nothing that walks the tree finishes while they are there.
+        for branches in cycles(stack) {
+            let detached = branches
+                .iter()
+                .find(|name| **name == stack.head_branch.name)
+                .unwrap_or(&branches[0])
+                .clone();
+            let root = root(stack).filter(|root| *root != detached);
+            if let Ok(branch) = stack.get_branch_mut(&detached) {
+                branch.parent = root;
+            }
+        }
+
+        let missing: Vec<String> = stack
+            .branches
+            .iter()
+            .filter(|branch| !exists(&branch.name))
+            .map(|branch| branch.name.clone())
+            .collect();
+        for name in missing {
+            let _ = stack.untrack_branch(&name);
+        }
+
+        // Branches whose parent is gone move onto the root, rather than starting a second tree.
+        let names: Vec<String> = stack.branches.iter().map(|branch| branch.name.clone()).collect();
+        let root = root(stack);
+        for branch in stack.branches.iter_mut() {
+            if branch.parent.as_ref().is_some_and(|parent| !names.contains(parent)) {
+                branch.parent = root.clone().filter(|root| *root != branch.name);
+            }
+        }
+        stack.refresh();
+    }
+    state.stacks.retain(|stack| !stack.branches.is_empty());
+
+    if diagnose(state, branches, detached)
+        .iter()
+        .any(|issue| matches!(issue, Issue::StaleContext { .. }))
+    {
+        state.clear_detached_head_context();
+    }
+}
+
+/// Groups of branches whose parent links form a loop, each starting with the member that comes
+/// first in the stack.
+fn cycles(stack: &data::Stack) -> Vec<Vec<String>> {
+    let position = |name: &str| stack.branches.iter().position(|branch| branch.name == name);
+    let mut cycles: Vec<Vec<String>> = Vec::new();
+
+    for start in &stack.branches {
+        let mut path: Vec<&str> = Vec::new();
+        let mut current = Some(start);
+        while let Some(branch) = current {
+            if let Some(index) = path.iter().position(|name| *name == branch.name) {
+                let mut cycle: Vec<String> = path[index..].iter().map(|name| name.to_string()).collect();
+                if !cycles.iter().any(|known| known.contains(&cycle[0])) {
+                    let first = (0..cycle.len()).min_by_key(|i| position(&cycle[*i])).unwrap_or(0);
+                    cycle.rotate_left(first);
+                    cycles.push(cycle);
+                }
+                break;
+            }
+            path.push(&branch.name);
+            current = branch
+                .parent
+                .as_deref()
+                .and_then(|parent| stack.branches.iter().find(|branch| branch.name == parent));
+        }
+    }
+    cycles
+}
+
+/// The branch at the bottom of a stack: its head branch if that has no parent, or else the first
+/// branch without one.
+fn root(stack: &data::Stack) -> Option<String> {
+    let roots: Vec<&data::StackBranch> = stack.branches.iter().filter(|branch| branch.parent.is_none()).collect();
+    roots
+        .iter()
+        .find(|branch| branch.name == stack.head_branch.name)
+        .or(roots.first())
+        .map(|branch| branch.name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{SolMetadata, Stack, StackBranch};
+
+    fn branch(name: &str, parent: Option<&str>, depth: u8) -> StackBranch {
+        let mut branch = StackBranch::new(name.to_string(), String::new());
+        if let Some(parent) = parent {
+            branch.set_parent(parent.to_string());
+        }
+        branch.depth = depth;
+        branch
+    }
+
+    #[test]
+    fn test_diagnose_and_repair() {
+        let mut first = Stack::new("a".to_string(), "main".to_string());
+        first.branches = vec![
+            branch("a", None, 0),
+            branch("b", Some("a"), 1),
+            branch("c", Some("b"), 1),
+            branch("d", Some("gone"), 1),
+        ];
+        let mut second = Stack::new("x".to_string(), "main".to_string());
+        second.branches = vec![branch("x", None, 0), branch("c", Some("x"), 1)];
+
+        let mut state = SolMetadata {
+            stacks: vec![first, second],
+            ..Default::default()
+        };
+        state.set_detached_head_context("a".to_string(), "b".to_string()).unwrap();
+
+        let git_branches: Vec<String> = ["main", "a", "c", "d", "x"].iter().map(|s| s.to_string()).collect();
+        let issues = diagnose(&state, &git_branches, false);
+        assert_eq!(
+            issues,
+            vec![
+                Issue::MissingBranch { stack: "a".into(), branch: "b".into() },
+                Issue::DepthMismatch { stack: "a".into(), branch: "c".into(), recorded: 1, actual: 2 },
+                Issue::DanglingParent { stack: "a".into(), branch: "d".into(), parent: "gone".into() },
+                Issue::Duplicate { branch: "c".into(), stack: "a".into(), also_in: "x".into() },
+                Issue::StaleContext { branch: "b".into() },
+            ]
+        );
+
+        repair(&mut state, &git_branches, false);
+        assert!(diagnose(&state, &git_branches, false).is_empty());
+        let stack = state.get_stack("a").unwrap();
+        assert_eq!(stack.get_branch("c").unwrap().parent.as_deref(), Some("a"));
+        assert_eq!(stack.get_branch("d").unwrap().parent.as_deref(), Some("a"));
+        assert!(!state.get_stack("x").unwrap().has_branch("c"));
+        assert!(state.get_detached_head_context().is_none());
+    }
+
+    #[test]
+    fn test_cycles_are_reported_and_broken() {
+        let mut stack = Stack::new("s".to_string(), "main".to_string());
+        stack.branches = vec![
+            branch("s", None, 0),
+            branch("a", Some("b"), 1),
+            branch("b", Some("a"), 2),
+            branch("c", Some("b"), 3),
+        ];
+        let mut state = SolMetadata {
+            stacks: vec![stack],
+            ..Default::default()
+        };
+
+        let git_branches: Vec<String> = ["main", "s", "a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            diagnose(&state, &git_branches, false),
+            vec![Issue::Cycle { stack: "s".into(), branches: vec!["a".into(), "b".into()] }]
+        );
+
+        repair(&mut state, &git_branches, false);
+        assert!(diagnose(&state, &git_branches, false).is_empty());
+        let stack = state.get_stack("s").unwrap();
+        assert_eq!(stack.get_branch("a").unwrap().parent.as_deref(), Some("s"));
+        assert_eq!(stack.get_branch("c").unwrap().depth, 3);
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::restack;
+use crate::config::Config;
+use crate::data::restack::{FoldPullRequests, RestackState};
+use crate::forge::Forge;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+pub async fn fold(keep: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let mut state = data::SolMetadata::load()?;
+    let snapshot = state.clone();
+    let current_branch = state.checked_out_branch()?;
+    let stack = state.get_current_stack()?;
+    let branch = stack.get_branch(&current_branch)?;
+    let parent = match branch.parent.clone() {
+        Some(parent) if stack.has_branch(&parent) => parent,
+        _ => {
+            return Err(anyhow!(
+                "'{}' sits directly on '{}'; there is no stack branch to fold it into.",
+                current_branch,
+                stack.parent_of(branch)
+            ));
+        }
+    };
+    if stack.needs_restack(branch) {
+        return Err(anyhow!(
+            "'{}' is behind '{}'. Run `zyra restack` first.",
+            current_branch,
+            parent
+        ));
+    }
+
+    // With --keep the child survives and absorbs its parent; otherwise the parent absorbs the child.
+    let (kept, removed) = if keep {
+        (current_branch.clone(), parent.clone())
+    } else {
+        (parent.clone(), current_branch.clone())
+    };
+    let removed_pr = stack.get_branch(&removed)?.pr_id();
+    // Branches that sat on the removed one move, and so do their pull requests.
+    let moved: Vec<String> = stack
+        .branches
+        .iter()
+        .filter(|branch| branch.parent.as_deref() == Some(removed.as_str()))
+        .map(|branch| branch.name.clone())
+        .collect();
+    let tip = git::commit::resolve(&current_branch)?;
+    let old_tips = [
+        (kept.clone(), git::commit::resolve(&kept)?),
+        (removed.clone(), git::commit::resolve(&removed)?),
+    ];
+
+    if !keep {
+        git::branch::update_ref(&parent, &tip)?;
+        git::branch::switch(&parent, false)?;
+    }
+    git::branch::delete(&removed, true)?;
+
+    let stack = state
+        .find_stack_mut(&current_branch)
+        .ok_or_else(|| anyhow!("No stack found for current branch."))?;
+    if keep {
+        // The child takes the parent's place, so the parent's other children now sit on it.
+        let parent_branch = stack.get_branch(&parent)?.clone();
+        for sibling in stack.branches.iter_mut() {
+            if sibling.parent.as_deref() == Some(parent.as_str()) && sibling.name != kept {
+                sibling.set_parent(kept.clone());
+            }
+        }
+        let kept_branch = stack.get_branch_mut(&kept)?;
+        kept_branch.parent = parent_branch.parent.clone();
+        if let Some(hash) = parent_branch.parent_hash() {
+            kept_branch.set_parent_hash(hash.to_string());
+        }
+        stack.remove_branch(&removed)?;
+        stack.refresh();
+    } else {
+        stack.untrack_branch(&removed)?;
+        stack.get_branch_mut(&kept)?.set_commit_hash(tip);
+    }
+
+    let pending = stack
+        .descendants(&kept)
+        .iter()
+        .map(|branch| branch.name.clone())
+        .collect();
+    let retargets: Vec<(String, u32, String)> = moved
+        .iter()
+        .filter_map(|name| {
+            let branch = stack.get_branch(name).ok()?;
+            Some((name.clone(), branch.pr_id()?, stack.parent_of(branch).to_string()))
+        })
+        .collect();
+    let stack_display = stack.simple_display();
+
+    if state
+        .get_detached_head_context()
+        .is_some_and(|context| context.branch_name == removed)
+    {
+        state.clear_detached_head_context();
+    }
+
+    println!(
+        "Folded '{}' into '{}'",
+        ui::paint(Role::Branch, &removed),
+        ui::paint(Role::Branch, &kept)
+    );
+    println!("Updated stack: {}", stack_display);
+
+    // Aborting a restack that stops on conflicts brings back the removed branch and its entry,
+    // so the forge is only told once the restack is done, here or by `zyra continue`.
+    let pull_requests = FoldPullRequests { removed, kept: kept.clone(), removed_pr, retargets };
+    let mut restack = RestackState::new("fold", &kept, pending);
+    restack.old_tips = old_tips.into_iter().collect();
+    restack.snapshot = Some(snapshot);
+    restack.fold = Some(pull_requests.clone());
+    restack::run(restack, &mut state)?;
+    update_pull_requests(&pull_requests).await;
+    Ok(())
+}
+
+/// Closes the removed branch's pull request and points the pull requests of the branches that
+/// moved at their new parents. Without a forge, says what to do by hand.
+pub async fn update_pull_requests(pull_requests: &FoldPullRequests) {
+    let FoldPullRequests { removed, kept, removed_pr, retargets } = pull_requests;
+    let config = Config::load().unwrap_or_default();
+    let forge = match Forge::connect(&config, config.remote()) {
+        Ok(forge) => forge,
+        Err(err) => {
+            println!("{} Could not reach the forge: {}", ui::paint(Role::Warning, "warning:"), err);
+            None
+        }
+    };
+
+    if let Some(pr) = *removed_pr {
+        let reason = format!("Folded into `{}`.", kept);
+        match &forge {
+            Some(forge) => match forge.close(pr, &reason).await {
+                Ok(()) => println!("Closed PR #{} for '{}'", pr, removed),
+                Err(err) => println!(
+                    "{} Could not close PR #{} for '{}': {}",
+                    ui::paint(Role::Warning, "warning:"),
+                    pr,
+                    removed,
+                    err
+                ),
+            },
+            None => println!(
+                "{} PR #{} for '{}' is no longer tracked; close it on the forge.",
+                ui::paint(Role::Warning, "note:"),
+                pr,
+                removed
+            ),
+        }
+    }
+
+    for (branch, pr, base) in retargets {
+        match &forge {
+            Some(forge) => match forge.set_base(*pr, base).await {
+                Ok(()) => println!("Retargeted PR #{} for '{}' to '{}'", pr, branch, base),
+                Err(err) => println!(
+                    "{} Could not retarget PR #{} for '{}' to '{}': {}",
+                    ui::paint(Role::Warning, "warning:"),
+                    pr,
+                    branch,
+                    base,
+                    err
+                ),
+            },
+            None => println!(
+                "{} PR #{} for '{}' targets '{}'; retarget it to '{}'.",
+                ui::paint(Role::Warning, "note:"),
+                pr,
+                branch,
+                removed,
+                base
+            ),
+        }
+    }
+}
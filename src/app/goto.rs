@@ -1,8 +1,44 @@
-use anyhow::Result;
-use colored::Colorize;
+use anyhow::{anyhow, Result};
+use crate::ui::{self, Role};
 
 use crate::{data, errors, git};
 
+/// Most suggestions listed when a name is ambiguous.
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Something `goto` can switch to by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// The name matched against the input.
+    pub name: String,
+    /// How the candidate is shown in suggestions.
+    pub label: String,
+    /// The branch to check out.
+    pub branch: String,
+}
+
+impl Candidate {
+    fn stack(stack: &data::Stack) -> Self {
+        Candidate {
+            name: stack.name.clone(),
+            label: format!("{} (stack)", stack.name),
+            branch: stack.head_branch.name.clone(),
+        }
+    }
+
+    fn branch(stack: &data::Stack, branch: &str, qualified: bool) -> Self {
+        Candidate {
+            name: branch.to_string(),
+            label: if qualified {
+                format!("{}:{}", stack.name, branch)
+            } else {
+                branch.to_string()
+            },
+            branch: branch.to_string(),
+        }
+    }
+}
+
 pub fn goto(name: &str) -> Result<()> {
     if !git::repo::is_repo()? {
         return Err(errors::GitError::NotGitRepository.into());
@@ -10,41 +46,213 @@ pub fn goto(name: &str) -> Result<()> {
 
     let mut state = data::SolMetadata::load()?;
 
+    // PR check, e.g. `#123`
+    if let Some(number) = name.strip_prefix('#') {
+        let number: u32 = number
+            .parse()
+            .map_err(|_| anyhow!("'{}' is not a pull request number", name))?;
+        let branch = state
+            .stacks
+            .iter()
+            .flat_map(|stack| stack.branches.iter())
+            .find(|branch| branch.pr_id() == Some(number))
+            .map(|branch| branch.name.clone())
+            .ok_or_else(|| anyhow!("No tracked branch has pull request #{}", number))?;
+        return switch_branch(&mut state, &branch);
+    }
+
+    // Stack and branch check, e.g. `payments:api`; either part may be abbreviated
+    if let Some((stack_query, branch_query)) = name.split_once(':') {
+        let stacks: Vec<Candidate> = state.stacks.iter().map(Candidate::stack).collect();
+        let stack = state.get_stack(&pick(stack_query, &stacks, "stack")?.name)?;
+        if branch_query.is_empty() {
+            let branch = stack.head_branch.name.clone();
+            return switch_branch(&mut state, &branch);
+        }
+
+        let branches: Vec<Candidate> = stack
+            .tree_order()
+            .into_iter()
+            .map(|branch| Candidate::branch(stack, &branch.name, true))
+            .collect();
+        let branch = pick(branch_query, &branches, "branch")?.branch.clone();
+        return switch_branch(&mut state, &branch);
+    }
+
     // Stack check
     if state.has_stack(name) {
         // We will get its root branch
         let stack = state.get_stack(name)?;
         git::branch::switch(&stack.head_branch.name, false)?;
-        println!("Switched to stack '{}'", name.blue());
+        state.clear_detached_head_context();
+        state.save()?;
+        println!("Switched to stack '{}'", ui::paint(Role::Branch, name));
         return Ok(());
     }
 
-    // We are here... so everything from this point on must be within the current stack
-    let current_stack = state.get_current_stack()?;
+    // Off a stack, names are looked up in every stack; on one, only in the current stack.
+    let current_stack = state.get_current_stack().ok();
 
     // Branch check
-    if state.has_branch(name) && current_stack.has_branch(name) {
+    if current_stack.is_some_and(|stack| stack.has_branch(name)) {
         // We will switch to this branch
-        git::branch::switch(name, false)?;
-        println!("Switched to branch '{}'", name.blue());
-        return Ok(());
+        return switch_branch(&mut state, name);
     }
 
-    // Commit check -- Must be within this current branch
-    if git::commit::is_commit(name) {
-        // Store current branch and stack context
-        let current_branch = git::branch::get_current_branch()?;
-        
+    // Commit check -- Must be within one of the current stack's branches. Anything else that
+    // resolves to a commit, such as a tag or a word like `cafe`, is looked up by name below.
+    if let Some(current_stack) = current_stack
+        && git::commit::is_commit(name)
+        && let Some(owner) = commit_owner(current_stack, name)
+    {
+        let stack_name = current_stack.name.clone();
+
         // Switch to the commit
         git::branch::switch_to_commit(name)?;
-        
+
         // Update state to track that we're in a commit within the current stack/branch
-        state.set_detached_head_context(current_stack.name.clone(), current_branch)?;
+        state.set_detached_head_context(stack_name, owner.clone())?;
         state.save()?;
-        
-        println!("Switched to commit '{}' (stack context preserved)", name.blue());
+
+        println!(
+            "Switched to commit '{}' of branch '{}' (stack context preserved)",
+            ui::paint(Role::Hash, name),
+            ui::paint(Role::Branch, &owner)
+        );
         return Ok(());
     }
 
-    Err(anyhow::anyhow!("Could not find stack, branch, or commit '{}'", name))
-}
\ No newline at end of file
+    // Partial names
+    let mut candidates: Vec<Candidate> = state.stacks.iter().map(Candidate::stack).collect();
+    for stack in &state.stacks {
+        if current_stack.is_none_or(|current| current.name == stack.name) {
+            for branch in stack.tree_order() {
+                candidates.push(Candidate::branch(stack, &branch.name, current_stack.is_none()));
+            }
+        }
+    }
+    if rank(name, &candidates).is_empty() {
+        return Err(anyhow!("Could not find stack, branch, or commit '{}'", name));
+    }
+    let branch = pick(name, &candidates, "stack or branch")?.branch.clone();
+    switch_branch(&mut state, &branch)
+}
+
+/// The branch a commit belongs to: the lowest branch of the stack that contains it.
+fn commit_owner(stack: &data::Stack, commit: &str) -> Option<String> {
+    stack
+        .tree_order()
+        .into_iter()
+        .find(|branch| {
+            git::commit::is_ancestor(commit, &branch.name).unwrap_or(false)
+                && !git::commit::is_ancestor(commit, stack.parent_of(branch)).unwrap_or(true)
+        })
+        .map(|branch| branch.name.clone())
+}
+
+/// Switches to a tracked branch, leaving any detached-HEAD context behind.
+fn switch_branch(state: &mut data::SolMetadata, name: &str) -> Result<()> {
+    git::branch::switch(name, false)?;
+    state.clear_detached_head_context();
+    state.save()?;
+    println!("Switched to branch '{}'", ui::paint(Role::Branch, name));
+    Ok(())
+}
+
+/// How well `name` matches `query`, ignoring case: an exact match beats a prefix, which beats a
+/// substring, which beats the query's characters merely appearing in order.
+fn score(query: &str, name: &str) -> Option<u8> {
+    let (query, name) = (query.to_lowercase(), name.to_lowercase());
+    if name == query {
+        Some(4)
+    } else if name.starts_with(&query) {
+        Some(3)
+    } else if name.contains(&query) {
+        Some(2)
+    } else {
+        let mut chars = name.chars();
+        query
+            .chars()
+            .all(|wanted| chars.any(|c| c == wanted))
+            .then_some(1)
+    }
+}
+
+/// Candidates matching `query`, best first; equal matches prefer the shorter name.
+pub fn rank<'a>(query: &str, candidates: &'a [Candidate]) -> Vec<(u8, &'a Candidate)> {
+    let mut ranked: Vec<(u8, &Candidate)> = candidates
+        .iter()
+        .filter_map(|candidate| score(query, &candidate.name).map(|score| (score, candidate)))
+        .collect();
+    ranked.sort_by(|(a, x), (b, y)| {
+        b.cmp(a)
+            .then(x.name.len().cmp(&y.name.len()))
+            .then(x.label.cmp(&y.label))
+    });
+    ranked
+}
+
+/// The single best match for `query`, or an error listing the closest candidates.
+pub fn pick<'a>(query: &str, candidates: &'a [Candidate], kind: &str) -> Result<&'a Candidate> {
+    let ranked = rank(query, candidates);
+    let Some((best, first)) = ranked.first() else {
+        return Err(anyhow!("No {} matches '{}'", kind, query));
+    };
+
+    // Matches of the best kind that lead to the same branch, like a stack and its root, agree.
+    if ranked
+        .iter()
+        .filter(|(score, _)| score == best)
+        .all(|(_, candidate)| candidate.branch == first.branch)
+    {
+        return Ok(first);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let suggestions: Vec<String> = ranked
+        .iter()
+        .filter(|(_, candidate)| seen.insert(&candidate.branch))
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| format!("  {}", candidate.label))
+        .collect();
+    Err(anyhow!(
+        "'{}' matches more than one {}. Did you mean one of these?\n{}",
+        query,
+        kind,
+        suggestions.join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str) -> Candidate {
+        Candidate {
+            name: name.to_string(),
+            label: name.to_string(),
+            branch: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_pick() {
+        let candidates: Vec<Candidate> = ["auth-api", "auth-ui", "billing", "docs"].iter().map(|n| candidate(n)).collect();
+
+        assert_eq!(pick("bill", &candidates, "branch").unwrap().name, "billing");
+        assert_eq!(pick("DOCS", &candidates, "branch").unwrap().name, "docs");
+        assert_eq!(pick("aapi", &candidates, "branch").unwrap().name, "auth-api");
+        assert!(pick("zzz", &candidates, "branch").is_err());
+
+        let ranked: Vec<&str> = rank("au", &candidates).iter().map(|(_, c)| c.name.as_str()).collect();
+        assert_eq!(ranked, vec!["auth-ui", "auth-api"]);
+        let err = pick("auth", &candidates, "branch").unwrap_err().to_string();
+        assert!(err.contains("  auth-ui\n  auth-api"), "{}", err);
+
+        // A stack and its root branch lead to the same place.
+        let mut stack = candidate("billing");
+        stack.label = "billing (stack)".to_string();
+        let candidates = vec![stack, candidate("billing")];
+        assert_eq!(pick("bil", &candidates, "branch").unwrap().branch, "billing");
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::{anyhow, Result};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::data::restack::RestackState;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// Hooks zyra installs, and whether git feeds them input on stdin.
+const HOOKS: [(&str, bool); 3] = [("post-commit", false), ("post-rewrite", true), ("post-checkout", false)];
+
+/// Marks a hook file as written by zyra.
+const MARKER: &str = "# Installed by zyra.";
+
+/// Suffix given to a hook that was there before zyra's; zyra's hook runs it first.
+const USER_SUFFIX: &str = ".user";
+
+pub fn install() -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let dir = git::repo::hooks_dir()?;
+    fs::create_dir_all(&dir)?;
+    let exe = std::env::current_exe()?;
+
+    // Check every hook before writing any, so a conflict leaves nothing half installed.
+    for (hook, _) in HOOKS {
+        let path = dir.join(hook);
+        let user = dir.join(format!("{}{}", hook, USER_SUFFIX));
+        if path.exists() && !is_ours(&path) && user.exists() {
+            return Err(anyhow!(
+                "Both {} and {} exist; merge them by hand before installing.",
+                path.display(),
+                user.display()
+            ));
+        }
+    }
+
+    for (hook, stdin) in HOOKS {
+        let path = dir.join(hook);
+        let user = dir.join(format!("{}{}", hook, USER_SUFFIX));
+
+        // Keep an existing hook of the user's, to be run by ours.
+        if path.exists() && !is_ours(&path) {
+            fs::rename(&path, &user)?;
+            println!("Kept the existing {} hook as {}", hook, user.display());
+        }
+
+        fs::write(&path, script(hook, stdin, &exe.to_string_lossy()))?;
+        make_executable(&path)?;
+        println!("Installed {}", ui::paint(Role::Branch, hook));
+    }
+    Ok(())
+}
+
+pub fn uninstall() -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let dir = git::repo::hooks_dir()?;
+    for (hook, _) in HOOKS {
+        let path = dir.join(hook);
+        if !path.exists() || !is_ours(&path) {
+            continue;
+        }
+        fs::remove_file(&path)?;
+
+        let user = dir.join(format!("{}{}", hook, USER_SUFFIX));
+        if user.exists() {
+            fs::rename(&user, &path)?;
+            println!("Removed {} and put back your own hook", ui::paint(Role::Branch, hook));
+        } else {
+            println!("Removed {}", ui::paint(Role::Branch, hook));
+        }
+    }
+    Ok(())
+}
+
+/// Entry point of the installed hooks: brings the metadata up to date after plain git commands.
+pub fn run(hook: &str) -> Result<()> {
+    // While zyra rebases, it records every branch it moves itself.
+    if RestackState::exists() || git::rebase::in_progress() {
+        return Ok(());
+    }
+
+    // Loading drops a detached-HEAD context the checkout no longer matches; that is saved too.
+    let before = serde_json::to_value(data::SolMetadata::load_stored()?)?;
+    let mut state = data::SolMetadata::load()?;
+    let behind = sync(&mut state);
+    if serde_json::to_value(&state)? != before {
+        state.save()?;
+    }
+
+    if hook != "post-checkout" && !behind.is_empty() {
+        let names: Vec<String> = behind.iter().map(|name| format!("'{}'", name)).collect();
+        eprintln!(
+            "zyra: {} need(s) a restack; run `zyra restack`.",
+            names.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Records the tip of every branch that moved. Branches built on a moved branch keep the commit
+/// they were based on, so a restack replays only their own commits; their names are returned.
+fn sync(state: &mut data::SolMetadata) -> BTreeSet<String> {
+    let mut behind = BTreeSet::new();
+
+    for stack in state.stacks.iter_mut() {
+        let moved: Vec<(String, String, String)> = stack
+            .tree_order()
+            .into_iter()
+            .filter_map(|branch| {
+                let tip = git::commit::resolve(&branch.name).ok()?;
+                (tip != branch.commit_hash()).then(|| (branch.name.clone(), branch.commit_hash().to_string(), tip))
+            })
+            .collect();
+
+        for (name, old_tip, tip) in moved {
+            let based_on = |hash: Option<&str>, branch: &str| {
+                hash.is_some_and(|hash| git::commit::is_ancestor(hash, branch).unwrap_or(false))
+            };
+
+            let children: Vec<String> = stack
+                .get_children(&name)
+                .unwrap_or_default()
+                .iter()
+                .filter(|child| !based_on(child.parent_hash(), &child.name))
+                .filter(|child| based_on(Some(&old_tip), &child.name))
+                .map(|child| child.name.clone())
+                .collect();
+            for child in children {
+                if let Ok(child) = stack.get_branch_mut(&child) {
+                    child.set_parent_hash(old_tip.clone());
+                }
+            }
+
+            // A branch rebased by hand onto its parent is now based on the parent's tip.
+            let parent = stack
+                .get_branch(&name)
+                .map(|branch| stack.parent_of(branch).to_string())
+                .unwrap_or_default();
+            let rebased = stack
+                .get_branch(&name)
+                .is_ok_and(|branch| !based_on(branch.parent_hash(), &name))
+                && git::commit::is_ancestor(&parent, &name).unwrap_or(false);
+            let parent_tip = git::commit::resolve(&parent).ok();
+
+            if let Ok(branch) = stack.get_branch_mut(&name) {
+                branch.set_commit_hash(tip);
+                if let Some(parent_tip) = parent_tip.filter(|_| rebased) {
+                    branch.set_parent_hash(parent_tip);
+                }
+            }
+
+            behind.extend(
+                stack
+                    .descendants(&name)
+                    .iter()
+                    .filter(|branch| stack.needs_restack(branch))
+                    .map(|branch| branch.name.clone()),
+            );
+        }
+    }
+
+    behind
+}
+
+fn is_ours(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content.contains(MARKER))
+}
+
+/// The hook script: runs the user's own hook, if any, then hands over to `zyra _hook`.
+fn script(hook: &str, stdin: bool, exe: &str) -> String {
+    // Hooks that read stdin get their own copy of it.
+    let (save, feed) = if stdin {
+        ("input=$(cat)\n", "printf '%s\\n' \"$input\" | ")
+    } else {
+        ("", "")
+    };
+    format!(
+        "#!/bin/sh\n\
+         {marker} Your own hook, if any, lives in {hook}{suffix} and runs first.\n\
+         {save}\
+         user_hook=\"$(dirname \"$0\")/{hook}{suffix}\"\n\
+         if [ -x \"$user_hook\" ]; then\n\
+         \x20   {feed}\"$user_hook\" \"$@\" || exit $?\n\
+         fi\n\
+         '{exe}' _hook {hook} \"$@\" || true\n",
+        marker = MARKER,
+        hook = hook,
+        suffix = USER_SUFFIX,
+        save = save,
+        feed = feed,
+        exe = exe.replace('\'', "'\\''"),
+    )
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
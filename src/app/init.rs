@@ -1,6 +1,9 @@
-use crate::{data::*, git};
+use crate::{config::Config, data::*, git};
 use anyhow::{anyhow, Result};
 
+/// Branch names tried, in order, when nothing else identifies the base branch.
+const COMMON_BASES: [&str; 4] = ["main", "master", "develop", "trunk"];
+
 pub fn init(name: String, base: Option<String>, verbose: bool) -> Result<()> {
     let mut metadata = SolMetadata::load()?;
 
@@ -13,27 +16,63 @@ pub fn init(name: String, base: Option<String>, verbose: bool) -> Result<()> {
     if metadata.has_branch(&name) {
         return Err(anyhow!("Branch already exists."));
     }
+    if git::branch::exists(&name) {
+        return Err(anyhow!(
+            "A git branch named '{}' already exists. Run `zyra track {}` to add it to a stack.",
+            name,
+            name
+        ));
+    }
+
+    let config = Config::load()?;
+    let remote = config.remote();
+    let base = match base.or(config.base_branch.clone()) {
+        Some(base) => base,
+        None => detect_base(remote)?,
+    };
 
-    // Need to check if the base branch actually exists in git.
-    let list = git::branch::get_branches(true)?;
-    if !list.contains(&base.clone().unwrap_or("main".to_string())) {
-        return Err(anyhow!("Base branch does not exist."));
+    // Need to check if the base branch actually exists in git, locally or on the remote.
+    if !git::branch::exists(&base) {
+        if !git::branch::remote_exists(remote, &base) {
+            return Err(anyhow!(
+                "Base branch '{}' does not exist locally or on '{}'.",
+                base,
+                remote
+            ));
+        }
+        git::branch::create_tracking(remote, &base)?;
     }
 
-    let stack = Stack::new(name.clone(), base.unwrap_or("main".to_string()));
+    // Create the git branch before recording the stack, so a failure leaves nothing behind.
+    git::branch::create_from(&name, &base)?;
+
+    let stack = Stack::new(name.clone(), base.clone());
     metadata.add_stack(&stack)?;
     metadata.save()?;
 
-    git::branch::switch(&name, true)?;
-    git::branch::set_upstream(&name)?;
-
     if verbose {
         println!(
             "[sol] Initialized new '{}' from base branch '{}'.",
-            name.clone(),
-            "main"
+            name, base
         );
     }
     println!("Created branch: {}", stack.head_branch.name);
     Ok(())
 }
+
+/// Works out the repository's default branch: the remote's HEAD first, then common names.
+fn detect_base(remote: &str) -> Result<String> {
+    if let Some(base) = git::branch::remote_default_branch(remote) {
+        return Ok(base);
+    }
+
+    COMMON_BASES
+        .iter()
+        .find(|name| git::branch::exists(name) || git::branch::remote_exists(remote, name))
+        .map(|name| name.to_string())
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not detect the base branch. Pass --base or run `zyra config set base_branch <branch>`."
+            )
+        })
+}
@@ -1,19 +1,44 @@
 use crate::{data, errors, git};
 use anyhow::Result;
 
-pub fn log(graph: bool, verbose: bool, json: bool, pretty: bool) -> Result<()> {
+pub fn log(options: data::display::DisplayOptions, json: bool, pretty: bool) -> Result<()> {
     if !git::repo::is_repo()? {
         return Err(errors::git::GitError::NotGitRepository.into());
     }
 
     let state = data::SolMetadata::load()?;
-    let current_stack = state.get_current_stack()?;
+    let current_branch = state.current_branch()?;
+    let detached_at = if state.is_in_detached_head() {
+        Some(git::commit::get_hash()?)
+    } else {
+        None
+    };
+    let current_stack = match state.get_current_stack() {
+        Ok(stack) => stack,
+        Err(err) => {
+            // On a base branch such as `main`, show every stack built on top of it instead.
+            let stacks: Vec<&data::Stack> = state
+                .stacks
+                .iter()
+                .filter(|stack| stack.base_branch() == current_branch && !stack.is_archived())
+                .collect();
+
+            if stacks.is_empty() || json {
+                return Err(err);
+            }
+
+            for stack in stacks {
+                println!("{}", stack.display(&current_branch, None, options));
+            }
+            return Ok(());
+        }
+    };
 
     if json {
-        println!("{}", current_stack.json(pretty));
+        println!("{}", current_stack.json(&current_branch, pretty)?);
         return Ok(());
     }
 
-    println!("{}", current_stack.display(graph));
+    println!("{}", current_stack.display(&current_branch, detached_at.as_deref(), options));
     Ok(())
 }
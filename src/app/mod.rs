@@ -1,6 +1,23 @@
+pub mod absorb;
 pub mod branch;
+pub mod config;
+pub mod create;
+pub mod doctor;
+pub mod fold;
+pub mod hooks;
 pub mod init;
 pub mod log;
+pub mod modify;
 pub mod prev;
+pub mod prompt;
+pub mod pull;
+pub mod push;
+pub mod rename;
+pub mod reorder;
+pub mod restack;
+pub mod split;
+pub mod stack;
 pub mod next;
-pub mod goto;
\ No newline at end of file
+pub mod goto;
+pub mod track;
+pub mod undo;
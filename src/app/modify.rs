@@ -0,0 +1,76 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::restack;
+use crate::data::restack::RestackState;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// How `modify` applies the staged changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Amend the branch's last commit, or commit if it has none yet.
+    Amend,
+    /// Add a new commit on top of the branch.
+    Commit,
+}
+
+pub fn modify(mode: Mode, message: Option<&str>, all: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let mut state = data::SolMetadata::load()?;
+    let current_branch = state.checked_out_branch()?;
+    let stack = state.get_current_stack()?;
+    let parent = stack.parent_of(stack.get_branch(&current_branch)?).to_string();
+    let descendants: Vec<String> = stack
+        .descendants(&current_branch)
+        .iter()
+        .map(|branch| branch.name.clone())
+        .collect();
+
+    if all {
+        git::commit::stage_all()?;
+    }
+    let staged = git::commit::has_staged_changes()?;
+
+    // Amending a branch without commits of its own would rewrite its parent's commit instead.
+    let own_commits = !git::commit::list(&parent, &current_branch)?.is_empty();
+    let mode = if mode == Mode::Amend && !own_commits { Mode::Commit } else { mode };
+
+    match mode {
+        Mode::Amend if !staged && message.is_none() => {
+            return Err(anyhow!("Nothing to modify. Stage changes or pass a new message with -m."));
+        }
+        Mode::Commit if !staged => {
+            return Err(anyhow!("No staged changes to commit. Stage changes first or pass --all."));
+        }
+        Mode::Commit if message.is_none() => {
+            return Err(anyhow!("A commit message is required for a new commit. Pass -m."));
+        }
+        _ => {}
+    }
+
+    let old_tip = git::commit::resolve(&current_branch)?;
+    let new_tip = match mode {
+        Mode::Amend => git::commit::amend(message)?,
+        Mode::Commit => git::commit::create(message.unwrap_or_default())?,
+    };
+
+    let stack = state
+        .find_stack_mut(&current_branch)
+        .ok_or_else(|| anyhow!("No stack found for current branch."))?;
+    stack.get_branch_mut(&current_branch)?.set_commit_hash(new_tip);
+
+    println!(
+        "{} '{}'",
+        if mode == Mode::Amend { "Amended" } else { "Committed to" },
+        ui::paint(Role::Branch, &current_branch)
+    );
+
+    let mut pending = RestackState::new("modify", &current_branch, descendants);
+    pending.old_tips.insert(current_branch.clone(), old_tip);
+    pending.staged_branch = Some(current_branch);
+    restack::run(pending, &mut state)
+}
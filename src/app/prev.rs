@@ -1,5 +1,5 @@
 use anyhow::Result;
-use colored::Colorize;
+use crate::ui::{self, Role};
 
 use crate::{data, errors, git};
 
@@ -10,44 +10,28 @@ pub fn prev() -> Result<()> {
 
     let mut state = data::SolMetadata::load()?;
 
-    // If we're in a detached HEAD state, handle that case
-    if state.is_in_detached_head() {
-        let context = state.get_detached_head_context().unwrap();
-        let stack = state.get_stack(&context.stack_name)?;
-        let current_idx = stack.branches.iter().position(|b| b.name == context.branch_name)
-            .ok_or_else(|| anyhow::anyhow!("Could not find branch '{}' in stack", context.branch_name))?;
-
-        if current_idx == 0 {
-            return Err(anyhow::anyhow!("Already at the first branch in the stack"));
-        }
-
-        let prev_branch_name = stack.branches[current_idx - 1].name.clone();
-        
-        // Switch to the branch first
-        git::branch::switch(&prev_branch_name, false)?;
-        
-        // Then update state
-        state.clear_detached_head_context();
-        state.save()?;
-        
-        println!("Switched to branch '{}'", prev_branch_name.blue());
-        return Ok(());
-    }
-
-    // Normal branch navigation
+    // While HEAD is detached, this is the branch recorded in the detached-HEAD context.
+    let current_branch = state.current_branch()?;
     let current_stack = state.get_current_stack()?;
-    let current_branch = git::branch::get_current_branch()?;
-    
-    let current_idx = current_stack.branches.iter().position(|b| b.name == current_branch)
+
+    // Walk the branches in the order `zyra log` shows them, following parent/child links.
+    let order = current_stack.tree_order();
+    let current_idx = order.iter().position(|b| b.name == current_branch)
         .ok_or_else(|| anyhow::anyhow!("Could not find branch '{}' in stack", current_branch))?;
 
     if current_idx == 0 {
         return Err(anyhow::anyhow!("Already at the first branch in the stack"));
     }
 
-    let prev_branch = &current_stack.branches[current_idx - 1];
-    git::branch::switch(&prev_branch.name, false)?;
-    println!("Switched to branch '{}'", prev_branch.name.blue());
-    
+    let prev_branch_name = order[current_idx - 1].name.clone();
+    git::branch::switch(&prev_branch_name, false)?;
+
+    // Leaving a detached HEAD behind
+    if state.is_in_detached_head() {
+        state.clear_detached_head_context();
+        state.save()?;
+    }
+
+    println!("Switched to branch '{}'", ui::paint(Role::Branch, &prev_branch_name));
     Ok(())
-}
\ No newline at end of file
+}
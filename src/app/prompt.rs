@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::{data, git};
+
+/// Template used when `--format` is not given.
+pub const DEFAULT_FORMAT: &str = "{stack}:{branch} ({details})";
+
+/// Where the last computed segment is kept, inside the git directory.
+const CACHE_FILE: &str = "zyra-prompt.json";
+
+/// What the prompt shows about the current branch.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub stack: String,
+    pub branch: String,
+    /// 1-based position of the branch in the stack, bottom first.
+    pub index: usize,
+    pub total: usize,
+    pub needs_restack: bool,
+    pub detached: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Cache {
+    key: String,
+    /// Tips of the branches the segment was worked out from: the branch and its parent.
+    #[serde(default)]
+    tips: BTreeMap<String, String>,
+    segment: Option<Segment>,
+}
+
+/// Prints the prompt segment for the current branch, or nothing when it is not in a stack.
+/// Failures of any kind also print nothing, so the shell prompt is never disturbed.
+pub fn prompt(format: &str) {
+    let Some(git_dir) = git_dir() else {
+        return;
+    };
+    let restacking = git_dir.join("zyra-restack.json").exists();
+    if let Some(segment) = segment(&git_dir) {
+        println!("{}", render(format, &segment, restacking));
+    }
+}
+
+/// Looks the segment up in the cache, or works it out and caches it. The cache is keyed on what
+/// HEAD points at, on when the metadata last changed and on the tips of the branch and its
+/// parent, which decide whether it needs a restack. Git only runs on a miss.
+fn segment(git_dir: &Path) -> Option<Segment> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?.trim().to_string();
+    let tip = match head.strip_prefix("ref: ") {
+        Some(reference) => read_ref(git_dir, reference).unwrap_or_default(),
+        None => head.clone(),
+    };
+    let metadata_path = git_dir.join("zyra-metadata.json");
+    let modified = fs::metadata(&metadata_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_nanos();
+    let key = format!("{} {} {}", head, tip, modified);
+
+    let cache_path = git_dir.join(CACHE_FILE);
+    if let Some(cache) = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<Cache>(&data).ok())
+        .filter(|cache| cache.key == key && cache.tips == tips(git_dir, cache.tips.keys()))
+    {
+        return cache.segment;
+    }
+
+    let state: data::SolMetadata = serde_json::from_str(&fs::read_to_string(&metadata_path).ok()?).ok()?;
+    let segment = compute(&state, &head);
+    let branches = segment.as_ref().and_then(|segment| {
+        let stack = state.find_stack(&segment.branch)?;
+        let parent = stack.parent_of(stack.get_branch(&segment.branch).ok()?).to_string();
+        Some([segment.branch.clone(), parent])
+    });
+    let tips = tips(git_dir, branches.iter().flatten());
+
+    if let Ok(data) = serde_json::to_string(&Cache { key, tips, segment: segment.clone() }) {
+        let _ = fs::write(cache_path, data);
+    }
+    segment
+}
+
+/// Works the segment out from the metadata. While HEAD is detached, the detached-HEAD context
+/// names the branch, as long as HEAD is still one of its commits; a plain `git switch` elsewhere
+/// leaves the context behind without updating it.
+fn compute(state: &data::SolMetadata, head: &str) -> Option<Segment> {
+    let (branch, detached) = match head.strip_prefix("ref: refs/heads/") {
+        Some(branch) => (branch.to_string(), false),
+        None => {
+            let branch = state.get_detached_head_context()?.branch_name.clone();
+            if !git::commit::is_ancestor(head, &branch).unwrap_or(false) {
+                return None;
+            }
+            (branch, true)
+        }
+    };
+    let stack = state.find_stack(&branch)?;
+    let order = stack.tree_order();
+    let index = order.iter().position(|candidate| candidate.name == branch)?;
+    Some(Segment {
+        stack: stack.name.clone(),
+        branch: branch.clone(),
+        index: index + 1,
+        total: order.len(),
+        needs_restack: stack.needs_restack(order[index]),
+        detached,
+    })
+}
+
+/// Fills in a template. `{details}` is the position followed by any notes, e.g.
+/// `3/5, needs restack`; `{status}` is the notes alone.
+pub fn render(format: &str, segment: &Segment, restacking: bool) -> String {
+    let mut notes = Vec::new();
+    if restacking {
+        notes.push("restacking");
+    } else if segment.needs_restack {
+        notes.push("needs restack");
+    }
+    if segment.detached {
+        notes.push("detached");
+    }
+    let status = notes.join(", ");
+    let position = format!("{}/{}", segment.index, segment.total);
+    let details = if status.is_empty() {
+        position.clone()
+    } else {
+        format!("{}, {}", position, status)
+    };
+
+    format
+        .replace("{stack}", &segment.stack)
+        .replace("{branch}", &segment.branch)
+        .replace("{index}", &segment.index.to_string())
+        .replace("{total}", &segment.total.to_string())
+        .replace("{position}", &position)
+        .replace("{status}", &status)
+        .replace("{details}", &details)
+}
+
+/// Finds the git directory without running git: `$GIT_DIR`, or a `.git` directory or file in
+/// the current directory or one of its parents.
+fn git_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("GIT_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+
+    let mut dir = std::env::current_dir().ok()?;
+    loop {
+        let candidate = dir.join(".git");
+        if candidate.is_dir() {
+            return Some(candidate);
+        }
+        if candidate.is_file() {
+            // Worktrees and submodules point at their git directory.
+            let content = fs::read_to_string(&candidate).ok()?;
+            let target = content.trim().strip_prefix("gitdir: ")?;
+            return Some(dir.join(target));
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// The current tips of the given local branches; missing branches are left out.
+fn tips<'a>(git_dir: &Path, branches: impl Iterator<Item = &'a String>) -> BTreeMap<String, String> {
+    branches
+        .filter_map(|branch| Some((branch.clone(), read_ref(git_dir, &format!("refs/heads/{}", branch))?)))
+        .collect()
+}
+
+/// Reads a ref from its loose file or from `packed-refs`.
+fn read_ref(git_dir: &Path, reference: &str) -> Option<String> {
+    if let Ok(hash) = fs::read_to_string(git_dir.join(reference)) {
+        return Some(hash.trim().to_string());
+    }
+    fs::read_to_string(git_dir.join("packed-refs"))
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let (hash, name) = line.split_once(' ')?;
+            (name == reference).then(|| hash.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut segment = Segment {
+            stack: "payments".to_string(),
+            branch: "api".to_string(),
+            index: 3,
+            total: 5,
+            needs_restack: true,
+            detached: false,
+        };
+        assert_eq!(render(DEFAULT_FORMAT, &segment, false), "payments:api (3/5, needs restack)");
+        assert_eq!(render(DEFAULT_FORMAT, &segment, true), "payments:api (3/5, restacking)");
+
+        segment.needs_restack = false;
+        assert_eq!(render(DEFAULT_FORMAT, &segment, false), "payments:api (3/5)");
+        assert_eq!(render("[{branch} {index} of {total}]{status}", &segment, false), "[api 3 of 5]");
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+
+use crate::app::{push, restack};
+use crate::config::Config;
+use crate::data::restack::RestackState;
+use crate::data::{self, BranchStatus};
+use crate::git::{self, rebase::Outcome};
+use crate::ui::{self, Role};
+use crate::errors;
+
+/// Brings the current stack's branches up to date with their remote branches, then restacks the
+/// branches above any that changed. Branches whose history diverged get their local commits
+/// rebased onto the remote ones, or with `ff_only`, are left alone and reported.
+pub fn pull(remote: Option<&str>, ff_only: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let remote = push::resolve_remote(remote)?;
+    let autostash = Config::load()?.autostash();
+    let mut state = data::SolMetadata::load()?;
+    let snapshot = state.clone();
+    let original_branch = state.current_branch()?;
+    let stack = state.get_current_stack()?;
+    let branches: Vec<(String, String, Option<String>)> = stack
+        .tree_order()
+        .iter()
+        .map(|branch| {
+            let parent = stack.parent_of(branch).to_string();
+            (branch.name.clone(), parent, branch.remote_hash().map(str::to_string))
+        })
+        .collect();
+    let base = stack.base_branch().to_string();
+    let stack_name = stack.name.clone();
+
+    git::remote::fetch(&remote)?;
+    let checked_out = git::branch::get_branch_name()?;
+
+    let mut old_tips = BTreeMap::new();
+    let mut diverged = Vec::new();
+    let mut failed = Vec::new();
+    for (name, parent, remote_hash) in &branches {
+        let Some(theirs) = git::remote::tracking_hash(&remote, name) else {
+            continue;
+        };
+        let remote_branch = ui::paint(Role::Remote, format!("{}/{}", remote, name));
+
+        // The branch's new tip once it has the remote commits, or None if it was left alone.
+        let mut integrate = || -> Result<Option<String>> {
+            let ours = git::commit::resolve(name)?;
+
+            // Nothing new on the remote since we last pushed or pulled, even if we rewrote the branch.
+            let unchanged = remote_hash.as_deref() == Some(theirs.as_str());
+            if unchanged || ours == theirs || git::commit::is_ancestor(&theirs, &ours)? {
+                return Ok(Some(ours));
+            }
+            if git::commit::is_ancestor(&ours, &theirs)? {
+                if *name == checked_out {
+                    git::branch::reset(&theirs, false)?;
+                } else {
+                    git::branch::update_ref(name, &theirs)?;
+                }
+                old_tips.insert(name.clone(), ours);
+                println!("Fast-forwarded {} to {}", ui::paint(Role::Branch, name), remote_branch);
+                return Ok(Some(theirs.clone()));
+            }
+            if ff_only {
+                return Ok(None);
+            }
+
+            // Replay the branch's own commits on top of the remote branch; any the remote already
+            // has become empty and are dropped.
+            match git::rebase::onto(&theirs, parent, name, autostash)? {
+                Outcome::Done => {
+                    old_tips.insert(name.clone(), ours);
+                    println!(
+                        "Rebased the local commits of {} onto {}",
+                        ui::paint(Role::Branch, name),
+                        remote_branch
+                    );
+                    Ok(Some(git::commit::resolve(name)?))
+                }
+                Outcome::Conflict => {
+                    git::rebase::abort()?;
+                    Ok(None)
+                }
+            }
+        };
+
+        // A failure on one branch is reported at the end, once what was done is saved.
+        let tip = match integrate() {
+            Ok(tip) => tip,
+            Err(err) => {
+                failed.push(format!("'{}': {}", name, err));
+                continue;
+            }
+        };
+        if tip.is_none() {
+            diverged.push(name.clone());
+        }
+
+        if let Some(branch) = state.find_stack_mut(name).and_then(|stack| stack.get_branch_mut(name).ok()) {
+            match tip {
+                Some(tip) => {
+                    branch.set_commit_hash(tip);
+                    branch.set_remote_hash(theirs);
+                    if matches!(branch.status, BranchStatus::Conflict) {
+                        branch.set_status(BranchStatus::Pending);
+                    }
+                }
+                None => branch.set_status(BranchStatus::Conflict),
+            }
+        }
+    }
+
+    // Branches whose commits have all landed on the remote base branch are merged.
+    if let Some(remote_base) = git::remote::tracking_hash(&remote, &base)
+        && let Some(stack) = state.get_stack_mut(&stack_name).ok()
+    {
+        for (name, _, _) in &branches {
+            let Ok(parent) = stack.get_branch(name).map(|branch| stack.parent_of(branch).to_string()) else {
+                continue;
+            };
+            let own_commits = git::commit::list(&parent, name).is_ok_and(|commits| !commits.is_empty());
+            if own_commits
+                && git::commit::is_ancestor(name, &remote_base).unwrap_or(false)
+                && let Ok(branch) = stack.get_branch_mut(name)
+            {
+                branch.set_status(BranchStatus::Merged);
+            }
+        }
+    }
+
+    state.save()?;
+
+    // Branches that moved, and everything above them, are rebased onto their parents.
+    let stack = state.get_stack(&stack_name)?;
+    let pending: Vec<String> = stack
+        .tree_order()
+        .iter()
+        .filter(|branch| {
+            stack
+                .ancestry(&branch.name)
+                .unwrap_or_default()
+                .iter()
+                .any(|ancestor| old_tips.contains_key(&ancestor.name))
+        })
+        .map(|branch| branch.name.clone())
+        .collect();
+
+    if old_tips.is_empty() && diverged.is_empty() && failed.is_empty() {
+        println!("Already up to date.");
+    }
+
+    let mut restack = RestackState::new("pull", &original_branch, pending);
+    restack.old_tips = old_tips;
+    restack.snapshot = Some(snapshot);
+    restack::run(restack, &mut state)?;
+
+    let mut problems = Vec::new();
+    if !diverged.is_empty() {
+        let names: Vec<String> = diverged.iter().map(|name| format!("'{}'", name)).collect();
+        problems.push(format!(
+            "{} changed both locally and on {}{}. Rebase each by hand with `git rebase {}/<branch>`, \
+             then run `zyra restack`.",
+            names.join(", "),
+            remote,
+            if ff_only { "" } else { ", and the changes conflict" },
+            remote
+        ));
+    }
+    if !failed.is_empty() {
+        problems.push(format!("Could not pull:\n  {}", failed.join("\n  ")));
+    }
+    if !problems.is_empty() {
+        return Err(anyhow!("{}", problems.join("\n")));
+    }
+    Ok(())
+}
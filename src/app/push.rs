@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::restack;
+use crate::config::Config;
+use crate::git::remote::Lease;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// Pushes every branch of the current stack, parents first. Each push is forced, but only if the
+/// remote branch is still where zyra last pushed it, so work pushed by someone else is never lost.
+pub fn push(remote: Option<&str>) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let remote = resolve_remote(remote)?;
+    let mut state = data::SolMetadata::load()?;
+    let stack = state.get_current_stack()?;
+    let branches: Vec<(String, Option<String>)> = stack
+        .tree_order()
+        .iter()
+        .map(|branch| (branch.name.clone(), branch.remote_hash().map(str::to_string)))
+        .collect();
+    for branch in stack.tree_order().iter().filter(|branch| stack.needs_restack(branch)) {
+        println!(
+            "{} '{}' needs a restack; pushing it as it is.",
+            ui::paint(Role::Warning, "warning:"),
+            branch.name
+        );
+    }
+
+    let mut pushed = 0;
+    for (name, remote_hash) in branches {
+        let tip = git::commit::resolve(&name)?;
+        let tracking = git::remote::tracking_hash(&remote, &name);
+
+        // Without a record of our own push, trust what the last fetch saw.
+        let expected = remote_hash.or_else(|| tracking.clone());
+
+        if tracking.as_deref() == Some(tip.as_str()) && expected.as_deref() == Some(tip.as_str()) {
+            println!("{} is up to date", ui::paint(Role::Branch, &name));
+        } else {
+            match git::remote::push_with_lease(&remote, &name, expected.as_deref())? {
+                Lease::Pushed => {
+                    println!(
+                        "Pushed {} to {}",
+                        ui::paint(Role::Branch, &name),
+                        ui::paint(Role::Remote, format!("{}/{}", remote, name))
+                    );
+                    pushed += 1;
+                }
+                // Nothing recorded and nothing fetched, yet the branch is there.
+                Lease::Stale if expected.is_none() => {
+                    return Err(anyhow!(
+                        "'{}/{}' already exists, but zyra has never seen it. Run `git fetch {}` and check \
+                         what it holds, then push again; nothing above '{}' was pushed.",
+                        remote,
+                        name,
+                        remote,
+                        name
+                    ));
+                }
+                Lease::Stale => {
+                    return Err(anyhow!(
+                        "'{}/{}' has changed since zyra last pushed it. Fetch it and check what changed, \
+                         then push again; nothing above '{}' was pushed.",
+                        remote,
+                        name,
+                        name
+                    ));
+                }
+            }
+        }
+
+        let upstream = format!("{}/{}", remote, name);
+        if git::branch::get_upstream(&name).as_deref() != Some(upstream.as_str()) {
+            git::branch::set_upstream(&name, &remote)?;
+        }
+
+        // Recorded per branch, so a failure further up keeps what was already pushed.
+        if let Some(stack) = state.find_stack_mut(&name) {
+            stack.get_branch_mut(&name)?.set_remote_hash(tip);
+        }
+        state.save()?;
+    }
+
+    println!("Pushed {} branch(es) to {}.", pushed, remote);
+    Ok(())
+}
+
+/// The remote named on the command line, or the configured one; it must exist.
+pub(crate) fn resolve_remote(remote: Option<&str>) -> Result<String> {
+    let remote = match remote {
+        Some(remote) => remote.to_string(),
+        None => Config::load()?.remote().to_string(),
+    };
+    if !git::remote::exists(&remote) {
+        return Err(anyhow!("Remote '{}' is not configured.", remote));
+    }
+    Ok(remote)
+}
@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::restack;
+use crate::config::Config;
+use crate::forge::Forge;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+pub async fn rename(old: &str, new: &str) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let mut state = data::SolMetadata::load()?;
+    if !state.has_branch(old) {
+        return Err(anyhow!("Branch '{}' is not tracked.", old));
+    }
+    if state.has_branch(new) || git::branch::exists(new) {
+        return Err(anyhow!("Branch '{}' already exists.", new));
+    }
+    if state.has_stack(new) {
+        return Err(anyhow!("A stack named '{}' already exists.", new));
+    }
+
+    // Find where the branch lives on the remote before git forgets its old upstream.
+    let remote = match git::branch::get_upstream(old).and_then(|upstream| {
+        upstream
+            .split_once('/')
+            .map(|(remote, branch)| (remote.to_string(), branch.to_string()))
+    }) {
+        Some(upstream) => Some(upstream),
+        None => {
+            let remote = Config::load()?.remote().to_string();
+            git::branch::remote_exists(&remote, old).then(|| (remote, old.to_string()))
+        }
+    };
+
+    git::branch::rename(old, new)?;
+
+    let stack = state
+        .find_stack_mut(old)
+        .ok_or_else(|| anyhow!("Branch '{}' is not tracked.", old))?;
+    stack.rename_branch(old, new)?;
+    let pr = stack.get_branch(new)?.pr_id();
+    let base = stack.parent_of(stack.get_branch(new)?).to_string();
+    let child_prs: Vec<(String, u32)> = stack
+        .get_children(new)?
+        .iter()
+        .filter_map(|child| child.pr_id().map(|pr| (child.name.clone(), pr)))
+        .collect();
+    let stack_display = stack.simple_display();
+
+    if let Some(context) = state.detached_head_context.as_mut() {
+        if context.branch_name == old {
+            context.branch_name = new.to_string();
+        }
+        if context.stack_name == old {
+            context.stack_name = new.to_string();
+        }
+    }
+    state.save()?;
+
+    println!(
+        "Renamed '{}' to '{}'",
+        ui::paint(Role::Branch, old),
+        ui::paint(Role::Branch, new)
+    );
+    println!("Updated stack: {}", stack_display);
+
+    let Some((remote, remote_branch)) = remote else {
+        return Ok(());
+    };
+    if let Err(err) = git::remote::push(&remote, new, true) {
+        println!(
+            "{} Could not push '{}' to '{}': {}",
+            ui::paint(Role::Warning, "warning:"),
+            new,
+            remote,
+            err
+        );
+        return Ok(());
+    }
+
+    // Pull requests have to move off the old remote branch before it is deleted, or the forge
+    // closes them.
+    let forge = match Forge::connect(&Config::load()?, &remote) {
+        Ok(forge) => forge,
+        Err(err) => {
+            println!("{} Could not reach the forge: {}", ui::paint(Role::Warning, "warning:"), err);
+            None
+        }
+    };
+    for (child, pr) in child_prs {
+        match &forge {
+            Some(forge) => match forge.set_base(pr, new).await {
+                Ok(()) => println!("Retargeted PR #{} for '{}' to '{}'", pr, child, new),
+                Err(err) => println!(
+                    "{} Could not retarget PR #{} for '{}': {}",
+                    ui::paint(Role::Warning, "warning:"),
+                    pr,
+                    child,
+                    err
+                ),
+            },
+            None => println!(
+                "{} PR #{} for '{}' targets '{}'; retarget it to '{}'.",
+                ui::paint(Role::Warning, "note:"),
+                pr,
+                child,
+                old,
+                new
+            ),
+        }
+    }
+
+    // Forges cannot change a pull request's head branch, so it has to be opened again.
+    if let Some(pr) = pr {
+        match &forge {
+            Some(forge) => match reopen(forge, pr, new, &base).await {
+                Ok(number) => {
+                    if let Some(stack) = state.find_stack_mut(new) {
+                        stack.get_branch_mut(new)?.set_pr_id(number);
+                    }
+                    state.save()?;
+                    println!("Reopened PR #{} from '{}' as #{}", pr, new, number);
+                }
+                Err(err) => println!(
+                    "{} Could not reopen PR #{} from '{}': {}",
+                    ui::paint(Role::Warning, "warning:"),
+                    pr,
+                    new,
+                    err
+                ),
+            },
+            None => println!(
+                "{} PR #{} was opened from '{}'; open a new one from '{}' and close it.",
+                ui::paint(Role::Warning, "note:"),
+                pr,
+                old,
+                new
+            ),
+        }
+    }
+
+    match git::remote::delete(&remote, &remote_branch) {
+        Ok(()) => println!(
+            "Renamed '{}/{}' to '{}/{}'",
+            remote, remote_branch, remote, new
+        ),
+        Err(err) => println!(
+            "{} Could not delete the old remote branch '{}/{}': {}",
+            ui::paint(Role::Warning, "warning:"),
+            remote,
+            remote_branch,
+            err
+        ),
+    }
+    Ok(())
+}
+
+/// Opens a copy of a pull request from the renamed branch and closes the original.
+async fn reopen(forge: &Forge, pr: u32, head: &str, base: &str) -> Result<u32> {
+    let original = forge.pull_request(pr).await?;
+    if original.state != "open" {
+        return Err(anyhow!("it is {}", original.state));
+    }
+    let number = forge
+        .open(head, base, &original.title, original.body.as_deref(), original.draft)
+        .await?;
+    forge
+        .close(pr, &format!("The branch was renamed to `{}`; continued in #{}.", head, number))
+        .await?;
+    Ok(number)
+}
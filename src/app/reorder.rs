@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::restack;
+use crate::data::restack::RestackState;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+pub fn reorder(order: &[String]) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let mut state = data::SolMetadata::load()?;
+    let current_branch = state.current_branch()?;
+    let stack = state.get_current_stack()?;
+
+    let branches = stack.tree_order();
+    if let Some(fork) = branches
+        .iter()
+        .find(|branch| stack.get_children(&branch.name).is_ok_and(|children| children.len() > 1))
+    {
+        return Err(anyhow!(
+            "Only linear stacks can be reordered, but '{}' has several children.",
+            fork.name
+        ));
+    }
+    if let Some(behind) = branches.iter().find(|branch| stack.needs_restack(branch)) {
+        return Err(anyhow!(
+            "'{}' is behind its parent. Run `zyra restack` first.",
+            behind.name
+        ));
+    }
+
+    let current_order: Vec<String> = branches.iter().map(|branch| branch.name.clone()).collect();
+    let new_order = if order.is_empty() {
+        edit(stack.base_branch(), &current_order)?
+    } else {
+        order.to_vec()
+    };
+
+    let mut expected = current_order.clone();
+    let mut given = new_order.clone();
+    expected.sort();
+    given.sort();
+    if expected != given {
+        return Err(anyhow!(
+            "The new order must list each branch of the stack exactly once: {}",
+            current_order.join(", ")
+        ));
+    }
+    if new_order == current_order {
+        println!("The stack order is unchanged.");
+        return Ok(());
+    }
+
+    // Each branch's own commits start at its old parent's tip, wherever the parent ends up.
+    let mut pending = RestackState::new("reorder", &current_branch, new_order.clone());
+    for branch in &branches {
+        pending
+            .bases
+            .insert(branch.name.clone(), git::commit::resolve(stack.parent_of(branch))?);
+    }
+    pending.snapshot = Some(state.clone());
+
+    let stack = state.get_current_stack_mut()?;
+    for (index, name) in new_order.iter().enumerate() {
+        let parent = index.checked_sub(1).map(|below| new_order[below].clone());
+        stack.get_branch_mut(name)?.parent = parent;
+    }
+    stack
+        .branches
+        .sort_by_key(|branch| new_order.iter().position(|name| *name == branch.name));
+    stack.refresh();
+
+    println!(
+        "Reordered stack '{}': {}",
+        ui::paint(Role::Title, &stack.name),
+        stack.simple_display()
+    );
+    restack::run(pending, &mut state)
+}
+
+/// Opens the branch list in an editor and reads back the new order.
+fn edit(base: &str, order: &[String]) -> Result<Vec<String>> {
+    let text = format!(
+        "{}\n\n# Branches from the bottom of the stack (on top of '{}') to the top.\n\
+         # Move the lines to reorder them; each branch is rebased onto the line above it.\n",
+        order.join("\n"),
+        base
+    );
+
+    let edited = inquire::Editor::new("Reorder the stack:")
+        .with_predefined_text(&text)
+        .with_file_extension(".txt")
+        .prompt()?;
+
+    Ok(edited
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
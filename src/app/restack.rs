@@ -0,0 +1,239 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::fold;
+use crate::config::Config;
+use crate::data::restack::RestackState;
+use crate::data::{self, BranchStatus};
+use crate::git::{self, rebase::Outcome};
+use crate::ui::{self, Role};
+use crate::errors;
+
+/// Rebases every branch of the current stack that is behind its parent.
+pub fn restack() -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    ensure_idle()?;
+
+    let mut state = data::SolMetadata::load()?;
+    let current_branch = state.current_branch()?;
+    let pending = state
+        .get_current_stack()?
+        .tree_order()
+        .iter()
+        .map(|branch| branch.name.clone())
+        .collect();
+
+    run(RestackState::new("restack", &current_branch, pending), &mut state)
+}
+
+/// Resumes a restack after conflicts have been resolved.
+pub async fn resume() -> Result<()> {
+    let mut restack = RestackState::load()?.ok_or_else(|| anyhow!("No restack in progress."))?;
+    let mut state = data::SolMetadata::load()?;
+
+    if git::rebase::in_progress() && git::rebase::resume()? == Outcome::Conflict {
+        return Err(conflict_error(restack.in_progress.as_deref().unwrap_or("HEAD")));
+    }
+
+    // The stopped branch has been rebased, either just now or by a manual `git rebase --continue`.
+    if let Some(branch) = restack.in_progress.take() {
+        record_rebased(&mut state, &branch)?;
+        restack.pending.retain(|name| *name != branch);
+    }
+
+    let operation = restack.operation.clone();
+    let pull_requests = restack.fold.clone();
+    run(restack, &mut state)?;
+    if let Some(pull_requests) = &pull_requests {
+        fold::update_pull_requests(pull_requests).await;
+    }
+    println!("Finished {}.", operation);
+    Ok(())
+}
+
+/// Abandons a restack, putting every branch back where it was before the operation.
+pub fn abort() -> Result<()> {
+    let restack = RestackState::load()?.ok_or_else(|| anyhow!("No restack in progress."))?;
+    let mut state = match restack.snapshot.clone() {
+        Some(snapshot) => snapshot,
+        None => data::SolMetadata::load()?,
+    };
+
+    if git::rebase::in_progress() {
+        git::rebase::abort()?;
+    }
+    git::branch::switch(&restack.original_branch, false)?;
+
+    for (branch, old_tip) in &restack.old_tips {
+        if *branch == restack.original_branch {
+            let soft = restack.staged_branch.as_deref() == Some(branch.as_str());
+            git::branch::reset(old_tip, soft)?;
+        } else {
+            git::branch::update_ref(branch, old_tip)?;
+        }
+
+        if let Some(stack) = state.find_stack_mut(branch) {
+            let stack_branch = stack.get_branch_mut(branch)?;
+            stack_branch.set_commit_hash(old_tip.clone());
+            stack_branch.set_status(BranchStatus::Pending);
+        }
+    }
+
+    if let Some(patch) = &restack.abort_patch {
+        restore_patch(patch)?;
+    }
+
+    state.save()?;
+    RestackState::clear()?;
+
+    println!(
+        "Aborted {}; {} branch(es) restored.",
+        restack.operation,
+        restack.old_tips.len()
+    );
+    Ok(())
+}
+
+/// Fails if a restack is waiting on `zyra continue` or `zyra abort`.
+pub fn ensure_idle() -> Result<()> {
+    if RestackState::exists() {
+        return Err(anyhow!(
+            "A restack is in progress. Run `zyra continue` or `zyra abort` first."
+        ));
+    }
+    Ok(())
+}
+
+/// Works through the pending branches, rebasing each onto its parent. On conflicts the state
+/// is saved and an error explains how to resume.
+pub fn run(mut restack: RestackState, state: &mut data::SolMetadata) -> Result<()> {
+    let autostash = Config::load()?.autostash();
+    let mut rebased = 0;
+
+    while let Some(branch) = restack.pending.first().cloned() {
+        let stack = state
+            .find_stack(&branch)
+            .ok_or_else(|| anyhow!("Branch '{}' is not tracked.", branch))?;
+        let stack_branch = stack.get_branch(&branch)?;
+        let parent = stack.parent_of(stack_branch).to_string();
+
+        // A branch moved to a new parent has to be replayed even if that parent is below it.
+        let moved = restack
+            .bases
+            .get(&branch)
+            .is_some_and(|base| git::commit::resolve(&parent).ok().as_ref() != Some(base));
+        if !moved && git::commit::is_ancestor(&parent, &branch)? {
+            restack.pending.remove(0);
+            continue;
+        }
+
+        // Replay only the branch's own commits: those after the parent's previous tip.
+        let old_base = [
+            restack.bases.get(&branch).map(String::as_str),
+            restack.old_tips.get(&parent).map(String::as_str),
+            stack_branch.parent_hash(),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|hash| git::commit::is_ancestor(hash, &branch).unwrap_or(false))
+        .map(str::to_string);
+        let old_base = match old_base {
+            Some(hash) => hash,
+            None => git::commit::merge_base(&parent, &branch)?,
+        };
+
+        let old_tip = git::commit::resolve(&branch)?;
+        restack.old_tips.entry(branch.clone()).or_insert(old_tip);
+
+        let outcome = match git::rebase::onto(&parent, &old_base, &branch, autostash) {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                state.save()?;
+                restack.save()?;
+                return Err(anyhow!(
+                    "Could not rebase '{}' onto '{}': {}\nFix the problem, then run `zyra continue` or `zyra abort`.",
+                    branch,
+                    parent,
+                    err
+                ));
+            }
+        };
+
+        match outcome {
+            Outcome::Done => {
+                record_rebased(state, &branch)?;
+                restack.pending.remove(0);
+                rebased += 1;
+            }
+            Outcome::Conflict => {
+                if let Some(stack) = state.find_stack_mut(&branch) {
+                    stack.get_branch_mut(&branch)?.set_status(BranchStatus::Conflict);
+                }
+                restack.in_progress = Some(branch.clone());
+                state.save()?;
+                restack.save()?;
+                return Err(conflict_error(&branch));
+            }
+        }
+    }
+
+    git::branch::switch(&restack.original_branch, false)?;
+    if let Some(patch) = &restack.finish_patch {
+        restore_patch(patch)?;
+    }
+    state.save()?;
+    RestackState::clear()?;
+
+    if rebased > 0 {
+        println!("Restacked {} branch(es).", rebased);
+    }
+    Ok(())
+}
+
+/// Stores a branch's new tip and the parent tip it is now based on.
+fn record_rebased(state: &mut data::SolMetadata, branch: &str) -> Result<()> {
+    let stack = state
+        .find_stack_mut(branch)
+        .ok_or_else(|| anyhow!("Branch '{}' is not tracked.", branch))?;
+    let parent = stack.parent_of(stack.get_branch(branch)?).to_string();
+
+    let stack_branch = stack.get_branch_mut(branch)?;
+    stack_branch.set_commit_hash(git::commit::resolve(branch)?);
+    stack_branch.set_parent_hash(git::commit::resolve(&parent)?);
+    if matches!(stack_branch.status, BranchStatus::Conflict) {
+        stack_branch.set_status(BranchStatus::Pending);
+    }
+    Ok(())
+}
+
+/// Stages a patch that was set aside for the restack. If it no longer applies it is saved to a
+/// file instead, so nothing is lost.
+fn restore_patch(patch: &str) -> Result<()> {
+    if git::diff::apply(patch, &["--index", "--unidiff-zero"]).is_ok() {
+        return Ok(());
+    }
+
+    let path = data::storage::git_dir_path("zyra-changes.patch")?;
+    std::fs::write(&path, patch)?;
+    println!(
+        "{} Your staged changes no longer apply cleanly; they were saved to {}.\nApply them with `git apply --3way {}`.",
+        ui::paint(Role::Warning, "warning:"),
+        path.display(),
+        path.display()
+    );
+    Ok(())
+}
+
+fn conflict_error(branch: &str) -> anyhow::Error {
+    let files = git::rebase::conflicted_files();
+    let mut message = format!(
+        "Restacking '{}' stopped on conflicts.",
+        ui::paint(Role::Branch, branch)
+    );
+    for file in files {
+        message.push_str(&format!("\n  {}", ui::paint(Role::Warning, file)));
+    }
+    message.push_str("\nResolve them, `git add` the files, then run `zyra continue` (or `zyra abort`).");
+    anyhow!(message)
+}
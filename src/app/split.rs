@@ -0,0 +1,322 @@
+use anyhow::{anyhow, Result};
+use inquire::{MultiSelect, Select, Text};
+
+use crate::app::{create, restack};
+use crate::data::restack::RestackState;
+use crate::git::commit::CommitSummary;
+use crate::git::diff::{FileDiff, Hunk};
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// How `split` divides a branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Between existing commits; no commit is rewritten.
+    Commit,
+    /// Between hunks of the branch's diff, committing each part anew.
+    Hunk,
+}
+
+/// Longest hunk excerpt shown when asking where a hunk goes.
+const MAX_PREVIEW_LINES: usize = 20;
+
+pub fn split(mode: Mode, at: &[String], names: &[String]) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let mut state = data::SolMetadata::load()?;
+    let current_branch = state.checked_out_branch()?;
+    let stack = state.get_current_stack()?;
+    let branch = stack.get_branch(&current_branch)?;
+    let parent = stack.parent_of(branch).to_string();
+
+    if stack.needs_restack(branch) {
+        return Err(anyhow!(
+            "'{}' is behind '{}'. Run `zyra restack` first.",
+            current_branch,
+            parent
+        ));
+    }
+
+    let mut commits = git::commit::list(&parent, &current_branch)?;
+    commits.reverse();
+    if commits.is_empty() {
+        return Err(anyhow!("'{}' has no commits of its own to split.", current_branch));
+    }
+
+    let children: Vec<String> = stack
+        .get_children(&current_branch)?
+        .iter()
+        .map(|child| child.name.clone())
+        .collect();
+    let old_tip = git::commit::resolve(&current_branch)?;
+
+    // The tip of each part, bottom to top. The original branch keeps the first part.
+    let tips = match mode {
+        Mode::Commit => commit_tips(&commits, at)?,
+        Mode::Hunk => hunk_tips(&parent, &current_branch, &commits)?,
+    };
+    let new_names = branch_names(&state, &current_branch, names, tips.len() - 1)?;
+    let top = new_names.last().cloned().unwrap_or_else(|| current_branch.clone());
+
+    for (name, tip) in new_names.iter().zip(&tips[1..]) {
+        git::branch::create_at(name, tip)?;
+    }
+    // The top part has the same tree as the old tip, so switching keeps any local changes.
+    git::branch::switch(&top, false)?;
+    git::branch::update_ref(&current_branch, &tips[0])?;
+
+    let stack = state
+        .find_stack_mut(&current_branch)
+        .ok_or_else(|| anyhow!("No stack found for current branch."))?;
+    stack.get_branch_mut(&current_branch)?.set_commit_hash(tips[0].clone());
+
+    let mut below = (current_branch.clone(), tips[0].clone());
+    for (name, tip) in new_names.iter().zip(&tips[1..]) {
+        let mut branch = data::StackBranch::new(name.clone(), tip.clone());
+        branch.set_parent(below.0);
+        branch.set_parent_hash(below.1);
+        stack.add_branch(branch)?;
+        below = (name.clone(), tip.clone());
+    }
+    for child in &children {
+        let child = stack.get_branch_mut(child)?;
+        child.set_parent(top.clone());
+        child.set_parent_hash(old_tip.clone());
+    }
+    stack.refresh();
+
+    let pending = stack
+        .descendants(&top)
+        .iter()
+        .map(|branch| branch.name.clone())
+        .collect();
+    let stack_display = stack.simple_display();
+
+    println!(
+        "Split '{}' into {} branches",
+        ui::paint(Role::Branch, &current_branch),
+        tips.len()
+    );
+    println!("Updated stack: {}", stack_display);
+
+    restack::run(RestackState::new("split", &top, pending), &mut state)
+}
+
+/// Picks the commits that end each lower part, from `--at` or interactively.
+fn commit_tips(commits: &[CommitSummary], at: &[String]) -> Result<Vec<String>> {
+    if commits.len() < 2 {
+        return Err(anyhow!(
+            "The branch has a single commit. Split it with --by-hunk instead."
+        ));
+    }
+    let candidates = &commits[..commits.len() - 1];
+
+    let mut points: Vec<usize> = if at.is_empty() {
+        MultiSelect::new("Where should new branches start? Pick the last commit of each lower branch:", labels(candidates))
+            .raw_prompt()?
+            .into_iter()
+            .map(|option| option.index)
+            .collect()
+    } else {
+        at.iter()
+            .map(|rev| {
+                let hash = git::commit::resolve(rev)?;
+                candidates
+                    .iter()
+                    .position(|commit| commit.hash == hash)
+                    .ok_or_else(|| anyhow!("'{}' is not a commit of this branch below its tip.", rev))
+            })
+            .collect::<Result<_>>()?
+    };
+    points.sort();
+    points.dedup();
+    if points.is_empty() {
+        return Err(anyhow!("No split points were chosen."));
+    }
+
+    let mut tips: Vec<String> = points.iter().map(|index| candidates[*index].hash.clone()).collect();
+    tips.push(commits.last().unwrap().hash.clone());
+    Ok(tips)
+}
+
+/// Asks which part each hunk belongs to, then commits the parts on top of each other.
+fn hunk_tips(parent: &str, branch: &str, commits: &[CommitSummary]) -> Result<Vec<String>> {
+    let files = git::diff::range(parent, branch)?;
+
+    // Hunks of text edits can be placed one by one; other file changes move as a whole.
+    let units: Vec<(usize, Option<usize>)> = files
+        .iter()
+        .enumerate()
+        .flat_map(|(file_index, file)| {
+            let hunks: Vec<Option<usize>> = if file.is_line_edit() {
+                (0..file.hunks.len()).map(Some).collect()
+            } else {
+                vec![None]
+            };
+            hunks.into_iter().map(move |hunk| (file_index, hunk))
+        })
+        .collect();
+    if units.len() < 2 {
+        return Err(anyhow!("The branch changes a single hunk; there is nothing to split."));
+    }
+
+    let mut parts: Vec<usize> = Vec::new();
+    for (number, (file_index, hunk_index)) in units.iter().enumerate() {
+        let file = &files[*file_index];
+        println!(
+            "\n{} {}",
+            ui::paint(Role::Title, format!("[{}/{}]", number + 1, units.len())),
+            ui::paint(Role::Branch, &file.path)
+        );
+        println!("{}", preview(file, hunk_index.map(|index| &file.hunks[index])));
+
+        let count = parts.iter().max().map_or(0, |max| max + 1);
+        let mut options: Vec<String> = (1..=count).map(|n| format!("Branch {}", n)).collect();
+        options.push(format!("Branch {} (new)", count + 1));
+        let choice = Select::new("Which branch should this change go to?", options)
+            .with_starting_cursor(parts.last().copied().unwrap_or(0))
+            .raw_prompt()?;
+        parts.push(choice.index);
+    }
+
+    let count = parts.iter().max().unwrap() + 1;
+    let subject = &commits.last().unwrap().subject;
+    let mut tips = Vec::new();
+    let mut previous = git::commit::resolve(parent)?;
+    for part in 0..count {
+        let patch = part_patch(&files, &units, &parts, part);
+        let tree = git::diff::apply_to_tree(parent, &patch)?;
+        let message = Text::new(&format!("Commit message for branch {}:", part + 1))
+            .with_default(subject)
+            .prompt()?;
+        previous = git::commit::commit_tree(&tree, &previous, &message)?;
+        tips.push(previous.clone());
+    }
+    Ok(tips)
+}
+
+/// The patch for one part: every change up to and including the part's own, applied to the
+/// parent. `parts` gives the part of each unit; units are files, or hunks of text edits.
+fn part_patch(files: &[FileDiff], units: &[(usize, Option<usize>)], parts: &[usize], part: usize) -> String {
+    let mut patch = String::new();
+    for (file_index, file) in files.iter().enumerate() {
+        let chosen = |unit: &(usize, Option<usize>)| {
+            unit.0 == file_index && parts[units.iter().position(|u| u == unit).unwrap()] <= part
+        };
+        if units.iter().any(|unit| chosen(unit) && unit.1.is_none()) {
+            patch.push_str(&file.render_all());
+            continue;
+        }
+        // Hunks keep their place in the parent; skipped hunks only move the new line numbers.
+        let hunks: Vec<(&Hunk, i64)> = units
+            .iter()
+            .filter(|unit| chosen(unit))
+            .filter_map(|unit| unit.1.map(|index| (&file.hunks[index], 0)))
+            .collect();
+        if !hunks.is_empty() {
+            patch.push_str(&file.render(&hunks));
+        }
+    }
+    patch
+}
+
+/// Names for the new branches above the original, from `--name` or prompted.
+fn branch_names(
+    state: &data::SolMetadata,
+    branch: &str,
+    names: &[String],
+    count: usize,
+) -> Result<Vec<String>> {
+    let mut chosen: Vec<String> = Vec::new();
+    for index in 0..count {
+        let name = match names.get(index) {
+            Some(name) => name.clone(),
+            None => {
+                let default = create::unique_name(state, &format!("{}-{}", branch, index + 2));
+                Text::new(&format!("Name for branch {}:", index + 2))
+                    .with_default(&default)
+                    .prompt()?
+            }
+        };
+        if state.has_branch(&name) || git::branch::exists(&name) || chosen.contains(&name) {
+            return Err(anyhow!("Branch '{}' already exists.", name));
+        }
+        chosen.push(name);
+    }
+    Ok(chosen)
+}
+
+fn labels(commits: &[CommitSummary]) -> Vec<String> {
+    commits
+        .iter()
+        .map(|commit| format!("{} {}", &commit.hash[..7.min(commit.hash.len())], commit.subject))
+        .collect()
+}
+
+fn preview(file: &FileDiff, hunk: Option<&Hunk>) -> String {
+    let text = match hunk {
+        Some(hunk) => hunk.render(0, 0),
+        None => file.header.clone(),
+    };
+    let mut lines: Vec<&str> = text.lines().take(MAX_PREVIEW_LINES).collect();
+    let hidden = text.lines().count().saturating_sub(lines.len());
+    let more = format!("... {} more line(s)", hidden);
+    if hidden > 0 {
+        lines.push(&more);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_part_patch_places_insertions_past_skipped_hunks() {
+        // Line 2 becomes three lines, and a line is inserted after line 8.
+        let patch = "diff --git a/f.txt b/f.txt\n\
+                     index 1111111..2222222 100644\n\
+                     --- a/f.txt\n\
+                     +++ b/f.txt\n\
+                     @@ -2 +2,3 @@\n\
+                     -two\n\
+                     +two\n\
+                     +two and a half\n\
+                     +two and three quarters\n\
+                     @@ -8,0 +11 @@\n\
+                     +INS\n";
+        let files = git::diff::parse(patch);
+        let units = [(0, Some(0)), (0, Some(1))];
+
+        // The insertion goes to the first part and the growing hunk to the second.
+        let parts = [1, 0];
+        assert!(part_patch(&files, &units, &parts, 0).ends_with("@@ -8,0 +9,1 @@\n+INS\n"));
+        let both = part_patch(&files, &units, &parts, 1);
+        assert!(both.contains("@@ -2,1 +2,3 @@\n"));
+        assert!(both.ends_with("@@ -8,0 +11,1 @@\n+INS\n"));
+
+        // Applied alone, the insertion lands after line 8.
+        let dir = tempfile::TempDir::new().unwrap();
+        let lines = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten"];
+        std::fs::write(dir.path().join("f.txt"), lines.join("\n") + "\n").unwrap();
+        let mut apply = std::process::Command::new("git")
+            .args(["apply", "--unidiff-zero", "-"])
+            .current_dir(dir.path())
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        apply
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(part_patch(&files, &units, &parts, 0).as_bytes())
+            .unwrap();
+        assert!(apply.wait().unwrap().success());
+        let applied = std::fs::read_to_string(dir.path().join("f.txt")).unwrap();
+        assert_eq!(applied.lines().nth(8), Some("INS"));
+    }
+}
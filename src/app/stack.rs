@@ -0,0 +1,180 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::restack;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// Lists the stacks; archived ones only with `all`.
+pub fn list(all: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let state = data::SolMetadata::load()?;
+    let current = state.get_current_stack().ok().map(|stack| stack.name.clone());
+    let stacks: Vec<&data::Stack> = state
+        .stacks
+        .iter()
+        .filter(|stack| all || !stack.is_archived())
+        .collect();
+
+    if stacks.is_empty() {
+        println!("No stacks yet. Run `zyra init` to start one.");
+        return Ok(());
+    }
+
+    let glyphs = ui::current().glyphs;
+    let width = stacks.iter().map(|stack| stack.name.len()).max().unwrap_or(0);
+    for stack in stacks {
+        let marker = if current.as_deref() == Some(stack.name.as_str()) {
+            ui::paint(Role::Current, glyphs.current)
+        } else {
+            ui::paint(Role::Muted, glyphs.other)
+        };
+        let archived = if stack.is_archived() {
+            format!(" {}", ui::paint(Role::Muted, "(archived)"))
+        } else {
+            String::new()
+        };
+
+        println!(
+            "{} {} {} {}{}",
+            marker,
+            ui::paint(Role::Branch, format!("{:width$}", stack.name)),
+            ui::paint(Role::Muted, format!("on {}", stack.base_branch())),
+            ui::paint(Role::Muted, format!("{} branch(es)", stack.branches.len())),
+            archived
+        );
+    }
+
+    let hidden = state.stacks.iter().filter(|stack| stack.is_archived()).count();
+    if !all && hidden > 0 {
+        println!("{}", ui::paint(Role::Muted, format!("{} archived stack(s) hidden; use --all to show them.", hidden)));
+    }
+    Ok(())
+}
+
+/// Renames a stack; its branches keep their names.
+pub fn rename(old: &str, new: &str) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let mut state = data::SolMetadata::load()?;
+    if state.has_stack(new) {
+        return Err(anyhow!("A stack named '{}' already exists.", new));
+    }
+    state.get_stack_mut(old)?.name = new.to_string();
+    if let Some(context) = state.detached_head_context.as_mut()
+        && context.stack_name == old
+    {
+        context.stack_name = new.to_string();
+    }
+    state.save()?;
+
+    println!(
+        "Renamed stack '{}' to '{}'",
+        ui::paint(Role::Title, old),
+        ui::paint(Role::Title, new)
+    );
+    Ok(())
+}
+
+/// Stops tracking a stack and, with `branches`, deletes its git branches too.
+pub fn delete(name: Option<&str>, branches: bool, yes: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+
+    let mut state = data::SolMetadata::load()?;
+    let name = resolve_name(&state, name)?;
+    let stack = state.get_stack(&name)?;
+    let branch_names: Vec<String> = stack.tree_order().iter().map(|branch| branch.name.clone()).collect();
+    let base = stack.base_branch().to_string();
+
+    if branches
+        && !yes
+        && !inquire::Confirm::new(&format!(
+            "Delete stack '{}' and its git branches ({})?",
+            name,
+            branch_names.join(", ")
+        ))
+        .with_default(false)
+        .prompt()?
+    {
+        println!("Nothing was deleted.");
+        return Ok(());
+    }
+
+    if branches {
+        // A branch cannot be deleted while it is checked out.
+        let current_branch = state.current_branch()?;
+        if branch_names.contains(&current_branch) {
+            git::branch::switch(&base, false)?;
+        }
+        for branch in branch_names.iter().rev() {
+            if git::branch::exists(branch) {
+                git::branch::delete(branch, true)?;
+            }
+        }
+    }
+
+    state.remove_stack(&name)?;
+    if state
+        .get_detached_head_context()
+        .is_some_and(|context| context.stack_name == name)
+    {
+        state.clear_detached_head_context();
+    }
+    state.save()?;
+
+    if branches {
+        println!(
+            "Deleted stack '{}' and {} branch(es)",
+            ui::paint(Role::Title, &name),
+            branch_names.len()
+        );
+    } else {
+        println!(
+            "Deleted stack '{}'; its git branches were left untouched.",
+            ui::paint(Role::Title, &name)
+        );
+    }
+    Ok(())
+}
+
+/// Archives a stack, hiding it from default views, or restores an archived one.
+pub fn archive(name: Option<&str>, archived: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let mut state = data::SolMetadata::load()?;
+    let name = resolve_name(&state, name)?;
+    let stack = state.get_stack_mut(&name)?;
+    if stack.is_archived() == archived {
+        return Err(anyhow!(
+            "Stack '{}' is {} archived.",
+            name,
+            if archived { "already" } else { "not" }
+        ));
+    }
+    stack.set_archived(archived);
+    state.save()?;
+
+    println!(
+        "{} stack '{}'",
+        if archived { "Archived" } else { "Restored" },
+        ui::paint(Role::Title, &name)
+    );
+    Ok(())
+}
+
+/// The named stack, or the current one when no name is given.
+fn resolve_name(state: &data::SolMetadata, name: Option<&str>) -> Result<String> {
+    match name {
+        Some(name) => Ok(state.get_stack(name)?.name.clone()),
+        None => Ok(state.get_current_stack()?.name.clone()),
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{anyhow, Result};
+
+use crate::config::Config;
+use crate::ui::{self, Role};
+use crate::{data, errors, git};
+
+/// Branch names treated as trunks when inferring parents, besides existing stack bases.
+const COMMON_BASES: [&str; 4] = ["main", "master", "develop", "trunk"];
+
+/// Where an untracked chain of branches attaches.
+enum Attach {
+    /// Below a branch already tracked in a stack.
+    Tracked(String),
+    /// On a base branch, as the root of a new stack.
+    Base(String),
+}
+
+pub fn track(name: &str, parent: Option<&str>) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let mut state = data::SolMetadata::load()?;
+
+    if state.has_branch(name) {
+        return Err(anyhow!("Branch '{}' is already tracked.", name));
+    }
+    if !git::branch::exists(name) {
+        return Err(anyhow!("Branch '{}' does not exist.", name));
+    }
+
+    let config = Config::load()?;
+    let bases = trunk_names(&state, &config);
+
+    // Walk down from the branch until we reach something tracked or a trunk, collecting
+    // the untracked branches in between so the whole chain is imported at once.
+    let mut chain = vec![name.to_string()];
+    let attach = loop {
+        let current = chain.last().unwrap();
+        let next = match (chain.len(), parent) {
+            (1, Some(parent)) => {
+                if !git::branch::exists(parent) {
+                    return Err(anyhow!("Parent branch '{}' does not exist.", parent));
+                }
+                parent.to_string()
+            }
+            _ => infer_parent(&state, &bases, current, &chain)?,
+        };
+
+        if state.has_branch(&next) {
+            break Attach::Tracked(next);
+        }
+        if bases.contains(&next) {
+            break Attach::Base(next);
+        }
+        chain.push(next);
+    };
+
+    // Add the chain bottom-up so each branch's parent already exists.
+    let mut previous = match &attach {
+        Attach::Tracked(parent) => Some(parent.clone()),
+        Attach::Base(base) => {
+            let root = chain.last().unwrap().clone();
+            if state.has_stack(&root) {
+                return Err(anyhow!("A stack named '{}' already exists.", root));
+            }
+
+            let mut stack = data::Stack::new(root.clone(), base.clone());
+            stack.branches[0].set_commit_hash(git::commit::resolve(&root)?);
+            stack.refresh();
+            state.add_stack(&stack)?;
+            chain.pop();
+            Some(root)
+        }
+    };
+
+    let stack = state
+        .find_stack_mut(previous.as_deref().unwrap())
+        .ok_or_else(|| anyhow!("Could not find the stack to track into."))?;
+    for branch_name in chain.iter().rev() {
+        let mut branch = data::StackBranch::new(branch_name.clone(), git::commit::resolve(branch_name)?);
+        if let Some(parent) = previous.take() {
+            branch.set_parent_hash(git::commit::resolve(&parent)?);
+            branch.set_parent(parent);
+        }
+        stack.add_branch(branch)?;
+        previous = Some(branch_name.clone());
+    }
+    stack.refresh();
+
+    let stack_name = stack.name.clone();
+    let stack_display = stack.simple_display();
+    state.save()?;
+
+    println!(
+        "Tracked '{}' in stack '{}'",
+        ui::paint(Role::Branch, name),
+        ui::paint(Role::Title, &stack_name)
+    );
+    println!("Updated stack: {}", stack_display);
+    Ok(())
+}
+
+pub fn untrack(name: &str) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let mut state = data::SolMetadata::load()?;
+    let stack = state
+        .find_stack_mut(name)
+        .ok_or_else(|| anyhow!("Branch '{}' is not tracked.", name))?;
+
+    stack.untrack_branch(name)?;
+    let stack_name = stack.name.clone();
+    if stack.branches.is_empty() {
+        state.remove_stack(&stack_name)?;
+    }
+
+    if state
+        .get_detached_head_context()
+        .is_some_and(|context| context.branch_name == name)
+    {
+        state.clear_detached_head_context();
+    }
+    state.save()?;
+
+    println!(
+        "Stopped tracking '{}'; the git branch was left untouched.",
+        ui::paint(Role::Branch, name)
+    );
+    Ok(())
+}
+
+/// Branches that stacks are built on: existing stack bases, the configured base, the remote's
+/// default branch and common trunk names.
+fn trunk_names(state: &data::SolMetadata, config: &Config) -> Vec<String> {
+    let mut bases: Vec<String> = state.stacks.iter().map(|s| s.base_branch().to_string()).collect();
+    bases.extend(config.base_branch.clone());
+    bases.extend(git::branch::remote_default_branch(config.remote()));
+    bases.extend(COMMON_BASES.iter().map(|name| name.to_string()));
+    bases.retain(|name| git::branch::exists(name));
+    bases.sort();
+    bases.dedup();
+    bases
+}
+
+/// Finds the closest branch below `name`: the candidate contained in `name` with the fewest
+/// commits between them. Tracked branches and trunks win ties so chains end as early as possible.
+fn infer_parent(
+    state: &data::SolMetadata,
+    bases: &[String],
+    name: &str,
+    chain: &[String],
+) -> Result<String> {
+    let mut best: Option<(u32, bool, String)> = None;
+
+    for candidate in git::branch::get_branches(true)? {
+        if chain.contains(&candidate) {
+            continue;
+        }
+        if !git::commit::is_ancestor(&candidate, name).unwrap_or(false) {
+            continue;
+        }
+
+        let (distance, _) = git::commit::ahead_behind(&candidate, name)?;
+        let settled = state.has_branch(&candidate) || bases.contains(&candidate);
+
+        // An untracked branch at the same commit could just as well sit above us.
+        if distance == 0 && !settled {
+            continue;
+        }
+
+        let better = match &best {
+            None => true,
+            Some((best_distance, best_settled, _)) => {
+                distance < *best_distance || (distance == *best_distance && settled && !best_settled)
+            }
+        };
+        if better {
+            best = Some((distance, settled, candidate));
+        }
+    }
+
+    best.map(|(_, _, candidate)| candidate).ok_or_else(|| {
+        anyhow!(
+            "Could not infer a parent for '{}'. Pass --parent to choose one.",
+            name
+        )
+    })
+}
@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::restack;
+use crate::data::journal::Journal;
+use crate::ui::{self, Role};
+use crate::{errors, git};
+
+/// Puts the branches and metadata back to how they were before the last operation.
+pub fn undo() -> Result<()> {
+    step(true)
+}
+
+/// Reapplies the last undone operation.
+pub fn redo() -> Result<()> {
+    step(false)
+}
+
+/// Lists recent operations, newest first.
+pub fn history(limit: usize) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+
+    let journal = Journal::load()?;
+    if journal.entries.is_empty() {
+        println!("No operations recorded yet.");
+        return Ok(());
+    }
+
+    let glyphs = ui::current().glyphs;
+    for (index, entry) in journal.entries.iter().enumerate().rev().take(limit) {
+        let marker = if index + 1 == journal.position {
+            ui::paint(Role::Current, glyphs.current)
+        } else {
+            ui::paint(Role::Muted, glyphs.other)
+        };
+        let undone = if index >= journal.position {
+            format!(" {}", ui::paint(Role::Muted, "(undone)"))
+        } else {
+            String::new()
+        };
+        let branches: Vec<&str> = entry.after.refs.keys().map(String::as_str).collect();
+
+        println!(
+            "{} {} {} {:<10} {}{}",
+            marker,
+            ui::paint(Role::Hash, format!("#{:<3}", entry.id)),
+            ui::paint(
+                Role::Muted,
+                entry.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string()
+            ),
+            entry.operation,
+            ui::paint(Role::Branch, branches.join(", ")),
+            undone
+        );
+    }
+    Ok(())
+}
+
+fn step(undo: bool) -> Result<()> {
+    if !git::repo::is_repo()? {
+        return Err(errors::GitError::NotGitRepository.into());
+    }
+    restack::ensure_idle()?;
+    if git::rebase::in_progress() {
+        return Err(anyhow!("A git rebase is in progress. Finish or abort it first."));
+    }
+    if git::commit::has_staged_changes()? || git::commit::has_unstaged_changes()? {
+        return Err(anyhow!(
+            "You have uncommitted changes. Commit or stash them before {}.",
+            if undo { "undoing" } else { "redoing" }
+        ));
+    }
+
+    let mut journal = Journal::load()?;
+    let index = if undo {
+        journal.position.checked_sub(1)
+    } else {
+        Some(journal.position).filter(|position| *position < journal.entries.len())
+    }
+    .ok_or_else(|| anyhow!("Nothing to {}.", if undo { "undo" } else { "redo" }))?;
+
+    let entry = &journal.entries[index];
+    let (from, to) = if undo {
+        (&entry.after, &entry.before)
+    } else {
+        (&entry.before, &entry.after)
+    };
+    let updates: Vec<(String, Option<String>, Option<String>)> = to
+        .refs
+        .iter()
+        .map(|(name, hash)| (name.clone(), hash.clone(), from.refs.get(name).cloned().flatten()))
+        .collect();
+
+    // The checked-out branch can only be moved once HEAD is off it.
+    let original_head = git::branch::head()?;
+    let moves_head = updates.iter().any(|(name, _, _)| *name == original_head);
+    if moves_head {
+        git::branch::detach()?;
+    }
+    let result = match git::branch::update_refs(&updates) {
+        Err(err) => Err(anyhow!(
+            "Could not {} '{}'; a branch has moved since it ran.\n{}",
+            if undo { "undo" } else { "redo" },
+            entry.operation,
+            err
+        )),
+        // Without the metadata the refs would disagree with it, so they go back too.
+        Ok(()) => to.metadata.save().inspect_err(|_| {
+            let reverse: Vec<(String, Option<String>, Option<String>)> = updates
+                .iter()
+                .map(|(name, new, old)| (name.clone(), old.clone(), new.clone()))
+                .collect();
+            let _ = git::branch::update_refs(&reverse);
+        }),
+    };
+    if let Err(err) = result {
+        if moves_head && git::branch::exists(&original_head) {
+            git::branch::switch(&original_head, false)?;
+        }
+        return Err(err);
+    }
+
+    if git::branch::exists(&to.head) {
+        git::branch::switch(&to.head, false)?;
+    } else if git::branch::commit_exists(&to.head)? {
+        git::branch::switch_to_commit(&to.head)?;
+    }
+
+    println!(
+        "{} '{}' (#{}); {} branch(es) restored",
+        if undo { "Undid" } else { "Redid" },
+        ui::paint(Role::Title, &entry.operation),
+        entry.id,
+        updates.len()
+    );
+
+    journal.position = if undo { index } else { index + 1 };
+    journal.save()
+}
@@ -0,0 +1,13 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Abort;
+
+impl Run for Abort {
+    async fn run(&self) -> Result<()> {
+        app::restack::abort()
+    }
+}
@@ -0,0 +1,17 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Absorb {
+    /// Skip the confirmation prompt
+    #[clap(short, long, default_value = "false")]
+    pub yes: bool,
+}
+
+impl Run for Absorb {
+    async fn run(&self) -> Result<()> {
+        app::absorb::absorb(self.yes)
+    }
+}
@@ -1,21 +1,37 @@
-use super::Run;
-use crate::app;
+use super::{completions, Run};
+use crate::app::{self, branch::Placement};
 use anyhow::Result;
 use clap::Parser;
+use clap_complete::ArgValueCandidates;
 
 #[derive(Parser, Debug)]
 pub struct Branch {
     pub name: String,
 
-    #[clap(short, long)]
+    #[clap(short, long, add = ArgValueCandidates::new(completions::branches))]
     pub from: Option<String>,
 
+    /// Insert the branch between the current branch and its children, which move onto it
+    #[clap(long, conflicts_with = "below")]
+    pub insert: bool,
+
+    /// Insert the branch between the current branch and its parent
+    #[clap(long)]
+    pub below: bool,
+
     #[clap(short, long, default_value = "false")]
     pub verbose: bool,
 }
 
 impl Run for Branch {
     async fn run(&self) -> Result<()> {
-        app::branch::branch(self.name.clone(), self.from.clone(), self.verbose)
+        let placement = if self.insert {
+            Placement::Insert
+        } else if self.below {
+            Placement::Below
+        } else {
+            Placement::Leaf
+        };
+        app::branch::branch(self.name.clone(), self.from.clone(), placement, self.verbose)
     }
 }
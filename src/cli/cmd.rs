@@ -10,6 +10,10 @@ pub enum Cmd {
     #[clap(alias = "b")]
     Branch(crate::cli::branch::Branch),
 
+    /// Create a new branch holding the staged changes as a commit
+    #[clap(alias = "c")]
+    Create(crate::cli::create::Create),
+
     /// Display the current stack
     #[clap(alias = "l")]
     Log(crate::cli::log::Log),
@@ -25,4 +29,77 @@ pub enum Cmd {
     /// Navigate to a branch, stack, or commit
     #[clap(alias = "g")]
     Goto(crate::cli::goto::Goto),
+
+    /// Apply staged changes to the current branch and restack the branches above it
+    #[clap(alias = "m")]
+    Modify(crate::cli::modify::Modify),
+
+    /// Fold staged hunks into the branches whose commits last touched those lines
+    #[clap(alias = "a")]
+    Absorb(crate::cli::absorb::Absorb),
+
+    /// Split the current branch into several stacked branches
+    Split(crate::cli::split::Split),
+
+    /// Merge the current branch into its parent
+    Fold(crate::cli::fold::Fold),
+
+    /// Change the order of the branches in a linear stack
+    Reorder(crate::cli::reorder::Reorder),
+
+    /// Rename a branch locally, on the remote, and in the stack
+    Rename(crate::cli::rename::Rename),
+
+    /// Push every branch in the stack, refusing to overwrite remote changes
+    Push(crate::cli::push::Push),
+
+    /// Bring in remote changes to the stack's branches and restack the branches above them
+    Pull(crate::cli::pull::Pull),
+
+    /// Rebase every branch in the stack onto its parent
+    #[clap(alias = "r")]
+    Restack(crate::cli::restack::Restack),
+
+    /// Continue a restack after resolving conflicts
+    Continue(crate::cli::r#continue::Continue),
+
+    /// Abort a restack and restore every branch
+    Abort(crate::cli::abort::Abort),
+
+    /// Undo the last operation, restoring every branch it changed
+    Undo(crate::cli::undo::Undo),
+
+    /// Redo the last undone operation
+    Redo(crate::cli::undo::Redo),
+
+    /// List recent operations that can be undone
+    History(crate::cli::undo::History),
+
+    /// List, rename, delete and archive stacks
+    Stack(crate::cli::stack::Stack),
+
+    /// Start managing an existing git branch, importing any untracked branches below it
+    Track(crate::cli::track::Track),
+
+    /// Stop managing a branch without deleting it
+    Untrack(crate::cli::untrack::Untrack),
+
+    /// Check the stack metadata against git and repair it
+    Doctor(crate::cli::doctor::Doctor),
+
+    /// Read and write configuration
+    Config(crate::cli::config::Config),
+
+    /// Install or remove the git hooks that keep the metadata in sync
+    Hooks(crate::cli::hooks::Hooks),
+
+    /// Print a short description of the current branch for a shell prompt
+    Prompt(crate::cli::prompt::Prompt),
+
+    /// Print a shell completion script that completes branch and stack names
+    Completions(crate::cli::completions::Completions),
+
+    /// Run by the installed git hooks
+    #[clap(name = "_hook", hide = true)]
+    Hook(crate::cli::hooks::Hook),
 }
@@ -0,0 +1,96 @@
+use super::Run;
+use crate::data;
+use anyhow::Result;
+use clap::{CommandFactory, Parser, ValueEnum};
+use clap_complete::env::{Bash, EnvCompleter, Fish, Powershell, Zsh};
+use clap_complete::CompletionCandidate;
+
+/// Environment variable the shell sets when it asks zyra for completions.
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+#[derive(Parser, Debug)]
+pub struct Completions {
+    /// The shell to print the completion script for
+    pub shell: Shell,
+}
+
+impl Run for Completions {
+    async fn run(&self) -> Result<()> {
+        let completer: &dyn EnvCompleter = match self.shell {
+            Shell::Bash => &Bash,
+            Shell::Zsh => &Zsh,
+            Shell::Fish => &Fish,
+            Shell::Powershell => &Powershell,
+        };
+
+        // The script calls back into this binary, which reads the metadata to complete names.
+        let command = super::Cmd::command();
+        let name = command.get_name();
+        let exe = std::env::current_exe()?;
+        completer.write_registration(
+            COMPLETE_VAR,
+            name,
+            name,
+            &exe.to_string_lossy(),
+            &mut std::io::stdout(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Tracked branches, with their stack as help.
+pub fn branches() -> Vec<CompletionCandidate> {
+    data::SolMetadata::load().map_or_else(|_| vec![], |state| branch_candidates(&state))
+}
+
+/// Stack names.
+pub fn stacks() -> Vec<CompletionCandidate> {
+    data::SolMetadata::load().map_or_else(|_| vec![], |state| stack_candidates(&state))
+}
+
+/// Everything `goto` accepts by name: stacks, branches, `stack:branch` and `#<PR number>`.
+pub fn goto_targets() -> Vec<CompletionCandidate> {
+    let Ok(state) = data::SolMetadata::load() else {
+        return vec![];
+    };
+
+    let mut candidates = stack_candidates(&state);
+    candidates.extend(branch_candidates(&state));
+    for stack in &state.stacks {
+        for branch in stack.tree_order() {
+            candidates.push(CompletionCandidate::new(format!("{}:{}", stack.name, branch.name)).hide(true));
+            if let Some(number) = branch.pr_id() {
+                candidates.push(CompletionCandidate::new(format!("#{}", number)).help(Some(branch.name.clone().into())));
+            }
+        }
+    }
+    candidates
+}
+
+fn branch_candidates(state: &data::SolMetadata) -> Vec<CompletionCandidate> {
+    state
+        .stacks
+        .iter()
+        .flat_map(|stack| {
+            stack.tree_order().into_iter().map(move |branch| {
+                CompletionCandidate::new(&branch.name).help(Some(format!("stack {}", stack.name).into()))
+            })
+        })
+        .collect()
+}
+
+fn stack_candidates(state: &data::SolMetadata) -> Vec<CompletionCandidate> {
+    state
+        .stacks
+        .iter()
+        .map(|stack| CompletionCandidate::new(&stack.name).help(Some("stack".into())))
+        .collect()
+}
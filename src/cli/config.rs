@@ -0,0 +1,41 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+pub struct Config {
+    #[clap(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective value of a key
+    Get {
+        key: String,
+    },
+
+    /// Set a key in the repository config, or the user config with --global
+    Set {
+        key: String,
+        value: String,
+
+        /// Write to the user config instead of the repository's .zyra.toml
+        #[clap(short, long, default_value_t = false)]
+        global: bool,
+    },
+
+    /// List every key with its effective value
+    List,
+}
+
+impl Run for Config {
+    async fn run(&self) -> Result<()> {
+        match &self.command {
+            ConfigCommand::Get { key } => app::config::get(key),
+            ConfigCommand::Set { key, value, global } => app::config::set(key, value, *global),
+            ConfigCommand::List => app::config::list(),
+        }
+    }
+}
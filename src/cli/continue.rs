@@ -0,0 +1,13 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Continue;
+
+impl Run for Continue {
+    async fn run(&self) -> Result<()> {
+        app::restack::resume().await
+    }
+}
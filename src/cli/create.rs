@@ -0,0 +1,27 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Create {
+    /// Name of the new branch; derived from the commit message when omitted
+    pub name: Option<String>,
+
+    /// The commit message
+    #[clap(short, long)]
+    pub message: String,
+
+    /// Stage every change, including untracked files, before committing
+    #[clap(short, long, default_value = "false")]
+    pub all: bool,
+
+    #[clap(short, long, default_value = "false")]
+    pub verbose: bool,
+}
+
+impl Run for Create {
+    async fn run(&self) -> Result<()> {
+        app::create::create(self.name.clone(), &self.message, self.all, self.verbose)
+    }
+}
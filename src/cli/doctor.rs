@@ -0,0 +1,17 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Doctor {
+    /// Repair the problems found instead of only reporting them
+    #[clap(long, default_value = "false")]
+    pub fix: bool,
+}
+
+impl Run for Doctor {
+    async fn run(&self) -> Result<()> {
+        app::doctor::doctor(self.fix)
+    }
+}
@@ -0,0 +1,17 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Fold {
+    /// Keep the current branch's name and remove its parent instead
+    #[clap(short, long, default_value = "false")]
+    pub keep: bool,
+}
+
+impl Run for Fold {
+    async fn run(&self) -> Result<()> {
+        app::fold::fold(self.keep).await
+    }
+}
@@ -1,11 +1,14 @@
 use anyhow::Result;
 use clap::Parser;
+use clap_complete::ArgValueCandidates;
 
+use super::completions;
 use crate::app;
 
 #[derive(Parser, Debug)]
 pub struct Goto {
-    /// The name of the branch, stack, or commit to switch to
+    /// The branch, stack or commit to switch to: a name or part of one, `#<PR number>`, or `stack:branch`
+    #[clap(add = ArgValueCandidates::new(completions::goto_targets))]
     pub name: String,
 }
 
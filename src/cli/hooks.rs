@@ -0,0 +1,44 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+pub struct Hooks {
+    #[clap(subcommand)]
+    pub command: HooksCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HooksCommand {
+    /// Install git hooks that keep the stack metadata in sync with plain git commands
+    Install,
+
+    /// Remove zyra's git hooks, putting back any hooks they replaced
+    Uninstall,
+}
+
+impl Run for Hooks {
+    async fn run(&self) -> Result<()> {
+        match &self.command {
+            HooksCommand::Install => app::hooks::install(),
+            HooksCommand::Uninstall => app::hooks::uninstall(),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Hook {
+    /// The git hook being run
+    pub name: String,
+
+    /// Arguments git passed to the hook
+    #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+impl Run for Hook {
+    async fn run(&self) -> Result<()> {
+        app::hooks::run(&self.name)
+    }
+}
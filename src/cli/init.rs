@@ -1,13 +1,14 @@
 use super::Run;
 use crate::git::*;
 use crate::{app::init, errors};
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use clap::Parser;
 
 #[derive(Parser, Debug)]
 pub struct Init {
     pub name: String,
 
+    /// Branch to build the stack on; detected from the remote or config when omitted
     #[clap(short, long)]
     pub base: Option<String>,
 
@@ -20,7 +21,6 @@ impl Run for Init {
         if !repo::is_repo()? {
             return Err(errors::git::GitError::NotGitRepository.into());
         }
-        println!("get_branch_name: {:?}", branch::get_branch_name()?);
         init::init(self.name.clone(), self.base.clone(), self.verbose)
     }
 }
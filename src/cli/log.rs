@@ -1,4 +1,5 @@
 use crate::app::log::log;
+use crate::data::display::DisplayOptions;
 use anyhow::Result;
 use clap::Parser;
 
@@ -10,9 +11,18 @@ pub struct Log {
     #[clap(short, long)]
     pub graph: bool,
 
+    /// Show each branch's commits and its ahead/behind counts against the remote
     #[clap(short, long, default_value_t = false)]
     pub verbose: bool,
 
+    /// Only show branches up to this depth, collapsing anything deeper
+    #[clap(short, long)]
+    pub depth: Option<u8>,
+
+    /// Show the newest branches at the top
+    #[clap(short, long, default_value_t = false, conflicts_with = "graph")]
+    pub reverse: bool,
+
     /// Show the stack in json format
     #[clap(short, long, default_value_t = false)]
     pub json: bool,
@@ -24,6 +34,12 @@ pub struct Log {
 
 impl Run for Log {
     async fn run(&self) -> Result<()> {
-        log(self.graph, self.verbose, self.json, self.pretty)
+        let options = DisplayOptions {
+            graph: self.graph,
+            verbose: self.verbose,
+            depth: self.depth,
+            reverse: self.reverse,
+        };
+        log(options, self.json, self.pretty)
     }
 }
@@ -2,27 +2,123 @@ pub use crate::cli::cmd::*;
 
 use anyhow::Result;
 
+pub mod abort;
+pub mod absorb;
 pub mod branch;
 mod cmd;
+pub mod completions;
+pub mod config;
+pub mod r#continue;
+pub mod create;
+pub mod doctor;
+pub mod fold;
+pub mod hooks;
 pub mod init;
 pub mod log;
+pub mod modify;
 pub mod prev;
+pub mod prompt;
+pub mod pull;
+pub mod push;
 pub mod next;
 pub mod goto;
+pub mod rename;
+pub mod reorder;
+pub mod restack;
+pub mod split;
+pub mod stack;
+pub mod track;
+pub mod undo;
+pub mod untrack;
 
+#[allow(async_fn_in_trait)]
 pub trait Run {
     async fn run(&self) -> Result<()>;
 }
 
 impl Run for Cmd {
     async fn run(&self) -> Result<()> {
-        match self {
+        // Snapshot the repository around commands that change it, so they can be undone.
+        let before = self
+            .operation()
+            .and_then(|_| crate::data::journal::Snapshot::capture().ok());
+
+        let result = match self {
             Cmd::Init(init) => init.run().await,
             Cmd::Branch(branch) => branch.run().await,
+            Cmd::Create(create) => create.run().await,
             Cmd::Prev(prev) => prev.run().await,
             Cmd::Log(log) => log.run().await,
             Cmd::Next(next) => next.run().await,
             Cmd::Goto(goto) => goto.run().await,
+            Cmd::Modify(modify) => modify.run().await,
+            Cmd::Absorb(absorb) => absorb.run().await,
+            Cmd::Split(split) => split.run().await,
+            Cmd::Fold(fold) => fold.run().await,
+            Cmd::Reorder(reorder) => reorder.run().await,
+            Cmd::Rename(rename) => rename.run().await,
+            Cmd::Push(push) => push.run().await,
+            Cmd::Pull(pull) => pull.run().await,
+            Cmd::Restack(restack) => restack.run().await,
+            Cmd::Continue(cont) => cont.run().await,
+            Cmd::Abort(abort) => abort.run().await,
+            Cmd::Undo(undo) => undo.run().await,
+            Cmd::Redo(redo) => redo.run().await,
+            Cmd::History(history) => history.run().await,
+            Cmd::Doctor(doctor) => doctor.run().await,
+            Cmd::Config(config) => config.run().await,
+            Cmd::Hooks(hooks) => hooks.run().await,
+            Cmd::Completions(completions) => completions.run().await,
+            Cmd::Prompt(prompt) => prompt.run().await,
+            Cmd::Hook(hook) => hook.run().await,
+            Cmd::Stack(stack) => stack.run().await,
+            Cmd::Track(track) => track.run().await,
+            Cmd::Untrack(untrack) => untrack.run().await,
+        };
+
+        if let (Some(operation), Some(before)) = (self.operation(), before)
+            && let Err(err) = crate::data::journal::Journal::record(operation, before)
+        {
+            eprintln!("warning: could not record '{}' for undo: {}", operation, err);
+        }
+        result
+    }
+}
+
+impl Cmd {
+    /// Name recorded in the undo journal, for commands that change branches or metadata.
+    fn operation(&self) -> Option<&'static str> {
+        match self {
+            Cmd::Init(_) => Some("init"),
+            Cmd::Branch(_) => Some("branch"),
+            Cmd::Create(_) => Some("create"),
+            Cmd::Modify(_) => Some("modify"),
+            Cmd::Absorb(_) => Some("absorb"),
+            Cmd::Split(_) => Some("split"),
+            Cmd::Fold(_) => Some("fold"),
+            Cmd::Reorder(_) => Some("reorder"),
+            Cmd::Rename(_) => Some("rename"),
+            Cmd::Pull(_) => Some("pull"),
+            Cmd::Restack(_) => Some("restack"),
+            Cmd::Continue(_) => Some("continue"),
+            Cmd::Abort(_) => Some("abort"),
+            Cmd::Stack(_) => Some("stack"),
+            Cmd::Track(_) => Some("track"),
+            Cmd::Untrack(_) => Some("untrack"),
+            Cmd::Doctor(_) => Some("doctor"),
+            Cmd::Log(_)
+            | Cmd::Prev(_)
+            | Cmd::Next(_)
+            | Cmd::Goto(_)
+            | Cmd::Push(_)
+            | Cmd::Undo(_)
+            | Cmd::Redo(_)
+            | Cmd::History(_)
+            | Cmd::Config(_)
+            | Cmd::Hooks(_)
+            | Cmd::Completions(_)
+            | Cmd::Prompt(_)
+            | Cmd::Hook(_) => None,
         }
     }
 }
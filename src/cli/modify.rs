@@ -0,0 +1,30 @@
+use super::Run;
+use crate::app::{self, modify::Mode};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Modify {
+    /// Amend the branch's last commit (the default)
+    #[clap(long, conflicts_with = "commit")]
+    pub amend: bool,
+
+    /// Add a new commit instead of amending
+    #[clap(short, long)]
+    pub commit: bool,
+
+    /// Message for the new commit, or a new message for the amended one
+    #[clap(short, long)]
+    pub message: Option<String>,
+
+    /// Stage every change, including untracked files, first
+    #[clap(short, long, default_value = "false")]
+    pub all: bool,
+}
+
+impl Run for Modify {
+    async fn run(&self) -> Result<()> {
+        let mode = if self.commit { Mode::Commit } else { Mode::Amend };
+        app::modify::modify(mode, self.message.as_deref(), self.all)
+    }
+}
@@ -0,0 +1,19 @@
+use super::Run;
+use crate::app::{self, prompt::DEFAULT_FORMAT};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Prompt {
+    /// Template with {stack}, {branch}, {index}, {total}, {position} (3/5), {status}
+    /// (e.g. "needs restack") and {details} (position and status)
+    #[clap(short, long, default_value = DEFAULT_FORMAT)]
+    pub format: String,
+}
+
+impl Run for Prompt {
+    async fn run(&self) -> Result<()> {
+        app::prompt::prompt(&self.format);
+        Ok(())
+    }
+}
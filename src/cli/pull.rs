@@ -0,0 +1,21 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Pull {
+    /// The remote to pull from; defaults to the `remote` setting, then `origin`
+    #[clap(short, long)]
+    pub remote: Option<String>,
+
+    /// Only fast-forward; report branches that diverged from the remote instead of rebasing them
+    #[clap(long, default_value_t = false)]
+    pub ff_only: bool,
+}
+
+impl Run for Pull {
+    async fn run(&self) -> Result<()> {
+        app::pull::pull(self.remote.as_deref(), self.ff_only)
+    }
+}
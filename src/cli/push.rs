@@ -0,0 +1,17 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Push {
+    /// The remote to push to; defaults to the `remote` setting, then `origin`
+    #[clap(short, long)]
+    pub remote: Option<String>,
+}
+
+impl Run for Push {
+    async fn run(&self) -> Result<()> {
+        app::push::push(self.remote.as_deref())
+    }
+}
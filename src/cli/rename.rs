@@ -0,0 +1,21 @@
+use super::{completions, Run};
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+use clap_complete::ArgValueCandidates;
+
+#[derive(Parser, Debug)]
+pub struct Rename {
+    /// The branch to rename
+    #[clap(add = ArgValueCandidates::new(completions::branches))]
+    pub old: String,
+
+    /// Its new name
+    pub new: String,
+}
+
+impl Run for Rename {
+    async fn run(&self) -> Result<()> {
+        app::rename::rename(&self.old, &self.new).await
+    }
+}
@@ -0,0 +1,18 @@
+use super::{completions, Run};
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+use clap_complete::ArgValueCandidates;
+
+#[derive(Parser, Debug)]
+pub struct Reorder {
+    /// The stack's branches in their new order, bottom to top (opens an editor if omitted)
+    #[clap(add = ArgValueCandidates::new(completions::branches))]
+    pub branches: Vec<String>,
+}
+
+impl Run for Reorder {
+    async fn run(&self) -> Result<()> {
+        app::reorder::reorder(&self.branches)
+    }
+}
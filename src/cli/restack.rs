@@ -0,0 +1,13 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Restack;
+
+impl Run for Restack {
+    async fn run(&self) -> Result<()> {
+        app::restack::restack()
+    }
+}
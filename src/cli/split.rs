@@ -0,0 +1,26 @@
+use super::Run;
+use crate::app::{self, split::Mode};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Split {
+    /// Split between hunks of the branch's changes instead of between commits
+    #[clap(long, default_value = "false")]
+    pub by_hunk: bool,
+
+    /// Last commit of a lower branch; repeat to split more than once (prompts if omitted)
+    #[clap(long, conflicts_with = "by_hunk")]
+    pub at: Vec<String>,
+
+    /// Names for the new branches, bottom to top (prompts for any missing)
+    #[clap(short, long = "name")]
+    pub names: Vec<String>,
+}
+
+impl Run for Split {
+    async fn run(&self) -> Result<()> {
+        let mode = if self.by_hunk { Mode::Hunk } else { Mode::Commit };
+        app::split::split(mode, &self.at, &self.names)
+    }
+}
@@ -0,0 +1,69 @@
+use super::{completions, Run};
+use crate::app;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use clap_complete::ArgValueCandidates;
+
+#[derive(Parser, Debug)]
+pub struct Stack {
+    #[clap(subcommand)]
+    pub command: StackCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StackCommand {
+    /// List the stacks in this repository
+    #[clap(alias = "ls")]
+    List {
+        /// Include archived stacks
+        #[clap(short, long, default_value_t = false)]
+        all: bool,
+    },
+
+    /// Rename a stack without renaming its branches
+    Rename {
+        #[clap(add = ArgValueCandidates::new(completions::stacks))]
+        old: String,
+        new: String,
+    },
+
+    /// Stop tracking a stack (the current one if no name is given)
+    Delete {
+        #[clap(add = ArgValueCandidates::new(completions::stacks))]
+        name: Option<String>,
+
+        /// Also delete the stack's git branches
+        #[clap(long, default_value_t = false)]
+        branches: bool,
+
+        /// Skip the confirmation prompt
+        #[clap(short, long, default_value_t = false)]
+        yes: bool,
+    },
+
+    /// Hide a stack from default views (the current one if no name is given)
+    Archive {
+        #[clap(add = ArgValueCandidates::new(completions::stacks))]
+        name: Option<String>,
+    },
+
+    /// Bring an archived stack back
+    Restore {
+        #[clap(add = ArgValueCandidates::new(completions::stacks))]
+        name: String,
+    },
+}
+
+impl Run for Stack {
+    async fn run(&self) -> Result<()> {
+        match &self.command {
+            StackCommand::List { all } => app::stack::list(*all),
+            StackCommand::Rename { old, new } => app::stack::rename(old, new),
+            StackCommand::Delete { name, branches, yes } => {
+                app::stack::delete(name.as_deref(), *branches, *yes)
+            }
+            StackCommand::Archive { name } => app::stack::archive(name.as_deref(), true),
+            StackCommand::Restore { name } => app::stack::archive(Some(name), false),
+        }
+    }
+}
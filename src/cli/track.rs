@@ -0,0 +1,21 @@
+use super::{completions, Run};
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+use clap_complete::ArgValueCandidates;
+
+#[derive(Parser, Debug)]
+pub struct Track {
+    /// The existing git branch to start managing
+    pub branch: String,
+
+    /// The branch it is based on; inferred from merge bases when omitted
+    #[clap(short, long, add = ArgValueCandidates::new(completions::branches))]
+    pub parent: Option<String>,
+}
+
+impl Run for Track {
+    async fn run(&self) -> Result<()> {
+        app::track::track(&self.branch, self.parent.as_deref())
+    }
+}
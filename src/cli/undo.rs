@@ -0,0 +1,35 @@
+use super::Run;
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct Undo;
+
+impl Run for Undo {
+    async fn run(&self) -> Result<()> {
+        app::undo::undo()
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Redo;
+
+impl Run for Redo {
+    async fn run(&self) -> Result<()> {
+        app::undo::redo()
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct History {
+    /// How many operations to show
+    #[clap(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+impl Run for History {
+    async fn run(&self) -> Result<()> {
+        app::undo::history(self.limit)
+    }
+}
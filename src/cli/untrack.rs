@@ -0,0 +1,18 @@
+use super::{completions, Run};
+use crate::app;
+use anyhow::Result;
+use clap::Parser;
+use clap_complete::ArgValueCandidates;
+
+#[derive(Parser, Debug)]
+pub struct Untrack {
+    /// The branch to stop managing; the git branch is kept
+    #[clap(add = ArgValueCandidates::new(completions::branches))]
+    pub branch: String,
+}
+
+impl Run for Untrack {
+    async fn run(&self) -> Result<()> {
+        app::track::untrack(&self.branch)
+    }
+}
@@ -0,0 +1,251 @@
+//! Layered configuration.
+//!
+//! Settings are read from the user file (`~/.config/zyra/config.toml`, or the platform
+//! equivalent) and then from `.zyra.toml` at the root of the repository; repository
+//! settings win. Anything left unset falls back to the defaults on the accessors.
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::git;
+
+/// Name of the per-repository configuration file.
+pub const REPO_FILE: &str = ".zyra.toml";
+
+/// Settings from one or more configuration files.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    /// Branch new stacks are based on when `--base` is omitted.
+    pub base_branch: Option<String>,
+    /// Remote branches are pushed to and fetched from.
+    pub remote: Option<String>,
+    /// Stash uncommitted changes around operations that rebase.
+    pub autostash: Option<bool>,
+    #[serde(skip_serializing_if = "is_unset")]
+    pub forge: ForgeConfig,
+    #[serde(skip_serializing_if = "is_unset")]
+    pub branch: BranchConfig,
+    #[serde(skip_serializing_if = "is_unset")]
+    pub ui: UiConfig,
+}
+
+/// Settings for the code hosting service.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ForgeConfig {
+    /// Hosting service, e.g. `github`.
+    pub provider: Option<String>,
+    /// API host, for self-hosted instances.
+    pub host: Option<String>,
+    /// Environment variable holding the API token.
+    pub token_env: Option<String>,
+}
+
+/// Settings for branches zyra creates.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct BranchConfig {
+    /// Template for branch names derived from commit messages.
+    pub template: Option<String>,
+}
+
+/// Settings for terminal output; environment variables take precedence.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct UiConfig {
+    pub theme: Option<String>,
+    pub color: Option<String>,
+    pub glyphs: Option<String>,
+}
+
+/// A configuration key that can be read and written with `zyra config`.
+pub struct Key {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Accepted values; empty when any string is allowed.
+    pub values: &'static [&'static str],
+}
+
+/// Every supported configuration key.
+pub const KEYS: &[Key] = &[
+    Key { name: "base_branch", description: "Base branch for new stacks", values: &[] },
+    Key { name: "remote", description: "Remote to push to and fetch from", values: &[] },
+    Key { name: "autostash", description: "Stash changes around rebases", values: &["true", "false"] },
+    Key { name: "forge.provider", description: "Code hosting service", values: &["github"] },
+    Key { name: "forge.host", description: "API host for self-hosted forges", values: &[] },
+    Key { name: "forge.token_env", description: "Environment variable holding the API token", values: &[] },
+    Key {
+        name: "branch.template",
+        description: "Branch name template; supports {message}, {user} and {date}",
+        values: &[],
+    },
+    Key { name: "ui.theme", description: "Colour theme", values: &crate::ui::Theme::NAMES },
+    Key { name: "ui.color", description: "When to use colour", values: &["auto", "always", "never"] },
+    Key { name: "ui.glyphs", description: "Glyph set", values: &["auto", "unicode", "ascii"] },
+];
+
+/// Which configuration file to act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    User,
+    Repo,
+}
+
+impl Config {
+    /// Loads and merges the user and repository configuration.
+    pub fn load() -> Result<Self> {
+        let user = Self::load_scope(Scope::User)?;
+        let repo = Self::load_scope(Scope::Repo)?;
+        Ok(repo.merge(user))
+    }
+
+    /// Loads a single configuration file; a missing file is empty.
+    pub fn load_scope(scope: Scope) -> Result<Self> {
+        match Self::path(scope) {
+            Some(path) if path.exists() => {
+                let data = fs::read_to_string(&path)?;
+                toml::from_str(&data).map_err(|err| anyhow!("Invalid config in {}: {}", path.display(), err))
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Path of a configuration file, if it can be determined.
+    pub fn path(scope: Scope) -> Option<PathBuf> {
+        match scope {
+            Scope::User => dirs::config_dir().map(|dir| dir.join("zyra").join("config.toml")),
+            Scope::Repo => git::repo::root().ok().map(|root| root.join(REPO_FILE)),
+        }
+    }
+
+    /// Fills in anything unset here from `fallback`.
+    pub fn merge(self, fallback: Config) -> Config {
+        Config {
+            base_branch: self.base_branch.or(fallback.base_branch),
+            remote: self.remote.or(fallback.remote),
+            autostash: self.autostash.or(fallback.autostash),
+            forge: ForgeConfig {
+                provider: self.forge.provider.or(fallback.forge.provider),
+                host: self.forge.host.or(fallback.forge.host),
+                token_env: self.forge.token_env.or(fallback.forge.token_env),
+            },
+            branch: BranchConfig {
+                template: self.branch.template.or(fallback.branch.template),
+            },
+            ui: UiConfig {
+                theme: self.ui.theme.or(fallback.ui.theme),
+                color: self.ui.color.or(fallback.ui.color),
+                glyphs: self.ui.glyphs.or(fallback.ui.glyphs),
+            },
+        }
+    }
+
+    /// Remote to push to and fetch from.
+    pub fn remote(&self) -> &str {
+        self.remote.as_deref().unwrap_or("origin")
+    }
+
+    /// Whether to stash uncommitted changes around rebases.
+    pub fn autostash(&self) -> bool {
+        self.autostash.unwrap_or(false)
+    }
+
+    /// Template for branch names derived from commit messages.
+    pub fn branch_template(&self) -> &str {
+        self.branch.template.as_deref().unwrap_or("{message}")
+    }
+
+    /// Reads a key as a string, or `None` when it is unset.
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let value = match find_key(key)?.name {
+            "base_branch" => self.base_branch.clone(),
+            "remote" => self.remote.clone(),
+            "autostash" => self.autostash.map(|value| value.to_string()),
+            "forge.provider" => self.forge.provider.clone(),
+            "forge.host" => self.forge.host.clone(),
+            "forge.token_env" => self.forge.token_env.clone(),
+            "branch.template" => self.branch.template.clone(),
+            "ui.theme" => self.ui.theme.clone(),
+            "ui.color" => self.ui.color.clone(),
+            "ui.glyphs" => self.ui.glyphs.clone(),
+            _ => unreachable!("every key in KEYS is handled"),
+        };
+        Ok(value)
+    }
+
+    /// Sets a key from a string, validating it first.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let key = find_key(key)?;
+        if !key.values.is_empty() && !key.values.contains(&value) {
+            return Err(anyhow!(
+                "Invalid value '{}' for '{}'. Expected one of: {}",
+                value,
+                key.name,
+                key.values.join(", ")
+            ));
+        }
+
+        let value = Some(value.to_string());
+        match key.name {
+            "base_branch" => self.base_branch = value,
+            "remote" => self.remote = value,
+            "autostash" => self.autostash = value.map(|value| value == "true"),
+            "forge.provider" => self.forge.provider = value,
+            "forge.host" => self.forge.host = value,
+            "forge.token_env" => self.forge.token_env = value,
+            "branch.template" => self.branch.template = value,
+            "ui.theme" => self.ui.theme = value,
+            "ui.color" => self.ui.color = value,
+            "ui.glyphs" => self.ui.glyphs = value,
+            _ => unreachable!("every key in KEYS is handled"),
+        }
+        Ok(())
+    }
+
+    /// Writes this configuration to the file for `scope`.
+    pub fn save_scope(&self, scope: Scope) -> Result<PathBuf> {
+        let path = Self::path(scope).ok_or_else(|| anyhow!("Could not determine the config file location."))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+fn is_unset<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+fn find_key(name: &str) -> Result<&'static Key> {
+    KEYS.iter()
+        .find(|key| key.name == name)
+        .ok_or_else(|| anyhow!("Unknown config key '{}'. Run `zyra config list` to see available keys.", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_overrides_user() {
+        let user: Config = toml::from_str("remote = \"upstream\"\nbase_branch = \"develop\"\n[ui]\ntheme = \"mono\"").unwrap();
+        let repo: Config = toml::from_str("base_branch = \"trunk\"").unwrap();
+        let config = repo.merge(user);
+
+        assert_eq!(config.base_branch.as_deref(), Some("trunk"));
+        assert_eq!(config.remote(), "upstream");
+        assert_eq!(config.get("ui.theme").unwrap().as_deref(), Some("mono"));
+        assert!(!config.autostash());
+    }
+
+    #[test]
+    fn test_set_validates_values() {
+        let mut config = Config::default();
+        config.set("autostash", "true").unwrap();
+        assert!(config.autostash());
+        assert!(config.set("autostash", "yes").is_err());
+        assert!(config.set("no.such.key", "x").is_err());
+    }
+}
@@ -1,26 +1,26 @@
-use super::{BranchStatus, Stack, StackBranch};
-use colored::Colorize;
-use serde::{Deserialize, Serialize};
+use super::{schema, BranchStatus, Stack, StackBranch};
+use crate::ui::{self, Role, Ui};
+use anyhow::Result;
 use std::fmt;
 
-#[derive(Serialize, Deserialize)]
-pub struct StackDisplay {
-    pub stack: String,
-    pub branches: Vec<BranchDisplay>,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct BranchDisplay {
-    pub name: String,
-    pub status: String,
-    pub commit: String,
+/// Options controlling how a stack is rendered by [`Stack::display`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayOptions {
+    /// Draw the stack as a tree instead of a list.
+    pub graph: bool,
+    /// Show each branch's commits and remote state.
+    pub verbose: bool,
+    /// Maximum branch depth to show; deeper branches are collapsed.
+    pub depth: Option<u8>,
+    /// Show the newest branches first (list view only).
+    pub reverse: bool,
 }
 
 impl Stack {
     /// Get the path from a branch to the head
     fn get_path_to_head(&self, start_branch: &str) -> Vec<&StackBranch> {
         let mut path = Vec::new();
-        let mut current = self.branches.iter().find(|b| &b.name == start_branch);
+        let mut current = self.branches.iter().find(|b| b.name == start_branch);
         
         while let Some(branch) = current {
             path.push(branch);
@@ -34,53 +34,195 @@ impl Stack {
         path
     }
 
-    /// Displays a full stack report.
-    pub fn display(&self, show_graph: bool) -> String {
-        let current_branch = crate::git::branch::get_branch_name().unwrap_or("main".to_string());
+    /// Markers appended to a branch line: restack state and, when verbose, remote ahead/behind.
+    fn branch_markers(&self, ui: &Ui, branch: &StackBranch, verbose: bool) -> String {
+        let mut markers = String::new();
+
+        if verbose {
+            if let Some(upstream) = crate::git::branch::get_upstream(&branch.name) {
+                match crate::git::commit::ahead_behind(&upstream, &branch.name) {
+                    Ok((0, 0)) => markers.push_str(&format!(" {}", ui.paint(Role::Muted, format!("[{}]", upstream)))),
+                    Ok((ahead, behind)) => markers.push_str(&format!(
+                        " {}",
+                        ui.paint(
+                            Role::Remote,
+                            format!("[{} {}{} {}{}]", upstream, ui.glyphs.ahead, ahead, ui.glyphs.behind, behind)
+                        )
+                    )),
+                    Err(_) => {}
+                }
+            } else {
+                markers.push_str(&format!(" {}", ui.paint(Role::Muted, "[no upstream]")));
+            }
+        }
+
+        if self.needs_restack(branch) {
+            markers.push_str(&format!(" {}", ui.paint(Role::Warning, "(needs restack)")));
+        }
+
+        markers
+    }
+
+    /// Lists the commits a branch has on top of its parent, one per line, marking `head`.
+    fn branch_commits(&self, ui: &Ui, branch: &StackBranch, prefix: &str, head: Option<&str>) -> String {
+        let commits = crate::git::commit::list(self.parent_of(branch), &branch.name).unwrap_or_default();
+        if commits.is_empty() {
+            return format!("{}{}\n", prefix, ui.paint(Role::Muted, "(no commits)"));
+        }
+
+        commits
+            .iter()
+            .map(|commit| {
+                format!(
+                    "{}{} {}{}\n",
+                    prefix,
+                    ui.paint(Role::Hash, commit.hash.get(..7).unwrap_or(&commit.hash)),
+                    commit.subject,
+                    if head == Some(commit.hash.as_str()) {
+                        format!(" {}", ui.paint(Role::Current, "(HEAD)"))
+                    } else {
+                        "".to_string()
+                    }
+                )
+            })
+            .collect()
+    }
+
+    /// Short form of a branch's tip, preferring the live git ref over the recorded hash.
+    fn short_hash(branch: &StackBranch) -> String {
+        let hash = crate::git::commit::resolve(&branch.name).unwrap_or_else(|_| branch.commit_hash.clone());
+        hash.get(..7).unwrap_or(&hash).to_string()
+    }
+
+    /// Number of branches hidden below a shown branch.
+    fn collapsed_count(&self, branch: &StackBranch, is_visible: &dyn Fn(&StackBranch) -> bool) -> usize {
+        self.get_children(&branch.name)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|child| !is_visible(child))
+            .map(|child| 1 + self.count_descendants(&child.name))
+            .sum()
+    }
+
+    /// Displays a full stack report. `detached_at` is the commit HEAD is detached on, within
+    /// `current_branch`.
+    pub fn display(&self, current_branch: &str, detached_at: Option<&str>, options: DisplayOptions) -> String {
+        self.render_at(ui::current(), current_branch, detached_at, options)
+    }
+
+    /// Renders a stack report with explicit settings and current branch.
+    pub fn render(&self, ui: &Ui, current_branch: &str, options: DisplayOptions) -> String {
+        self.render_at(ui, current_branch, None, options)
+    }
+
+    /// Marks the current branch's line with the commit HEAD is detached on.
+    fn detached_marker(ui: &Ui, branch: &StackBranch, current_branch: &str, detached_at: Option<&str>) -> String {
+        match detached_at {
+            Some(hash) if branch.name == current_branch => format!(
+                " {}",
+                ui.paint(Role::Warning, format!("(detached at {})", hash.get(..7).unwrap_or(hash)))
+            ),
+            _ => "".to_string(),
+        }
+    }
+
+    /// Renders a stack report, marking the commit HEAD is detached on, if any.
+    fn render_at(&self, ui: &Ui, current_branch: &str, detached_at: Option<&str>, options: DisplayOptions) -> String {
+        let verbose = options.verbose;
+        let glyphs = ui.glyphs;
 
         let mut display = String::new();
-        display.push_str(&format!("{}  {}\n", "[zyra]".bright_purple(), format!("Stack: {}", self.name).bold()));
+        display.push_str(&format!(
+            "{}  {}\n",
+            ui.paint(Role::Brand, "[zyra]"),
+            ui.paint(Role::Title, format!("Stack: {}", self.name))
+        ));
 
-        if !show_graph {
-            // Get the path from current branch to head
-            let path = self.get_path_to_head(&current_branch);
-            
-            // Display the path in reverse order (from head to current)
-            for (i, branch) in path.iter().rev().enumerate() {
-                let active = if branch.name == current_branch {
-                    "●".bright_green().bold()
-                } else {
-                    "○".dimmed()
-                };
-
-                // Format commit hash with brackets
-                let commit_hash = format!("[{}]", 
-                    branch.commit_hash
-                        .to_string()
-                        .get(..7)
-                        .unwrap_or(&branch.commit_hash)
-                ).blue();
-
-                // Format status if not pending
-                let status = if !matches!(branch.status, BranchStatus::Pending) {
-                    format!(" {}", branch.status.to_string().green())
-                } else {
-                    "".to_string()
-                };
-
-                display.push_str(&format!(
-                    "   {} {} {}{}\n",
-                    active,
-                    branch.name.yellow().bold(),
-                    commit_hash,
-                    status
-                ));
-
-                // Add a separator line between branches
-                if i < path.len() - 1 {
-                    display.push_str("   │\n");
-                }
+        if !options.graph {
+            let within_depth = |branch: &StackBranch| {
+                options
+                    .depth
+                    .is_none_or(|max| self.calculate_branch_depth(&branch.name) <= max)
+            };
+
+            // The path from the head to the current branch is highlighted; when we are not on a
+            // stack branch there is no path, so everything is expanded.
+            let path = self.get_path_to_head(current_branch);
+            let on_path = |name: &str| path.iter().any(|b| b.name == name);
+            let below_current = |branch: &StackBranch| {
+                self.get_path_to_head(&branch.name)
+                    .iter()
+                    .any(|b| b.name == current_branch)
+            };
+
+            // Branches hanging off the current path are shown, but their subtrees are collapsed.
+            let is_visible = |branch: &StackBranch| {
+                within_depth(branch)
+                    && (path.is_empty()
+                        || branch.parent.as_deref().is_none_or(&on_path)
+                        || below_current(branch))
+            };
+
+            let mut blocks: Vec<String> = self
+                .tree_order()
+                .into_iter()
+                .filter(|branch| is_visible(branch))
+                .map(|branch| {
+                    let active = if branch.name == current_branch {
+                        ui.paint(Role::Current, glyphs.current)
+                    } else {
+                        ui.paint(Role::Muted, glyphs.other)
+                    };
+
+                    let name = if on_path(&branch.name) {
+                        ui.paint(Role::Branch, &branch.name)
+                    } else {
+                        ui.paint(Role::OtherBranch, &branch.name)
+                    };
+
+                    // Format status if not pending
+                    let status = if !matches!(branch.status, BranchStatus::Pending) {
+                        format!(" {}", ui.paint(Role::Status, branch.status.to_string()))
+                    } else {
+                        "".to_string()
+                    };
+
+                    let collapsed = match self.collapsed_count(branch, &is_visible) {
+                        0 => "".to_string(),
+                        n => format!(" {}", ui.paint(Role::Muted, format!("(+{} collapsed)", n))),
+                    };
+
+                    let indent = "  ".repeat(self.calculate_branch_depth(&branch.name) as usize);
+                    let mut block = format!(
+                        "   {} {}{} {}{}{}{}\n",
+                        active,
+                        indent,
+                        name,
+                        ui.paint(Role::Hash, format!("[{}]", Self::short_hash(branch))),
+                        status,
+                        self.branch_markers(ui, branch, verbose)
+                            + &Self::detached_marker(ui, branch, current_branch, detached_at),
+                        collapsed
+                    );
+
+                    if verbose {
+                        block.push_str(&self.branch_commits(
+                            ui,
+                            branch,
+                            &format!("   {}    {}", glyphs.pipe, indent),
+                            detached_at,
+                        ));
+                    }
+                    block
+                })
+                .collect();
+
+            if options.reverse {
+                blocks.reverse();
             }
+
+            // Add a separator line between branches
+            display.push_str(&blocks.join(&format!("   {}\n", glyphs.pipe)));
             return display;
         }
 
@@ -88,63 +230,86 @@ impl Stack {
         let mut children_map: std::collections::HashMap<Option<String>, Vec<&StackBranch>> = std::collections::HashMap::new();
         for branch in &self.branches {
             children_map.entry(branch.parent.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(branch);
         }
 
+        struct GraphContext<'a> {
+            stack: &'a Stack,
+            ui: &'a Ui,
+            children_map: std::collections::HashMap<Option<String>, Vec<&'a StackBranch>>,
+            current_branch: &'a str,
+            detached_at: Option<&'a str>,
+            verbose: bool,
+            max_depth: Option<u8>,
+        }
+
         fn display_branch(
+            ctx: &GraphContext,
             branch: &StackBranch,
-            children_map: &std::collections::HashMap<Option<String>, Vec<&StackBranch>>,
-            current_branch: &str,
             prefix: &str,
             is_last: bool,
             display: &mut String
         ) {
-            let active = if branch.name == current_branch {
-                "●".bright_green().bold()
+            let ui = ctx.ui;
+            let active = if branch.name == ctx.current_branch {
+                ui.paint(Role::Current, ui.glyphs.current)
             } else {
-                "○".dimmed()
+                ui.paint(Role::Muted, ui.glyphs.other)
             };
-            let branch_symbol = if is_last { "└──" } else { "├──" };
+            let branch_symbol = if is_last { ui.glyphs.elbow } else { ui.glyphs.tee };
             
             // Format commit hash with brackets
-            let commit_hash = format!("[{}]", 
-                branch.commit_hash
-                    .to_string()
-                    .get(..7)
-                    .unwrap_or(&branch.commit_hash)
-                ).blue();
+            let commit_hash = ui.paint(Role::Hash, format!("[{}]", Stack::short_hash(branch)));
 
             // Format status if not pending
             let status = if !matches!(branch.status, BranchStatus::Pending) {
-                format!(" {}", branch.status.to_string().green())
+                format!(" {}", ui.paint(Role::Status, branch.status.to_string()))
             } else {
                 "".to_string()
             };
 
+            // Children past the depth limit are collapsed into a count.
+            let children = ctx.children_map.get(&Some(branch.name.clone()));
+            let collapsed = match (children, ctx.max_depth) {
+                (Some(_), Some(max)) if ctx.stack.calculate_branch_depth(&branch.name) >= max => {
+                    format!(
+                        " {}",
+                        ui.paint(Role::Muted, format!("(+{} collapsed)", ctx.stack.count_descendants(&branch.name)))
+                    )
+                }
+                _ => "".to_string(),
+            };
+
             display.push_str(&format!(
-                "{} {}{}{} {} {}{}\n",
+                "{} {}{}{} {} {}{}{}{}\n",
                 active,
                 prefix,
                 branch_symbol,
-                branch.name.yellow().bold(),
+                ui.paint(Role::Branch, &branch.name),
                 commit_hash,
                 status,
-                if branch.parent.is_none() { " (root)".dimmed() } else { "".into() }
+                if branch.parent.is_none() { ui.paint(Role::Muted, " (root)") } else { "".into() },
+                ctx.stack.branch_markers(ui, branch, ctx.verbose)
+                    + &Stack::detached_marker(ui, branch, ctx.current_branch, ctx.detached_at),
+                collapsed
             ));
 
-            if let Some(children) = children_map.get(&Some(branch.name.clone())) {
-                let child_prefix = if is_last {
-                    format!("{}    ", prefix)
-                } else {
-                    format!("{}│   ", prefix)
-                };
+            let child_prefix = if is_last {
+                format!("{}    ", prefix)
+            } else {
+                format!("{}{}   ", prefix, ui.glyphs.pipe)
+            };
 
+            if ctx.verbose {
+                display.push_str(&ctx.stack.branch_commits(ui, branch, &format!("  {}", child_prefix), ctx.detached_at));
+            }
+
+            if let Some(children) = children.filter(|_| collapsed.is_empty()) {
                 for (i, child) in children.iter().enumerate() {
                     display_branch(
+                        ctx,
                         child,
-                        children_map,
-                        current_branch,
                         &child_prefix,
                         i == children.len() - 1,
                         display
@@ -153,12 +318,21 @@ impl Stack {
             }
         }
 
-        if let Some(root_branches) = children_map.get(&None) {
+        let ctx = GraphContext {
+            stack: self,
+            ui,
+            children_map,
+            current_branch,
+            detached_at,
+            verbose,
+            max_depth: options.depth,
+        };
+
+        if let Some(root_branches) = ctx.children_map.get(&None) {
             for (i, branch) in root_branches.iter().enumerate() {
                 display_branch(
+                    &ctx,
                     branch,
-                    &children_map,
-                    &current_branch,
                     "",
                     i == root_branches.len() - 1,
                     &mut display
@@ -169,44 +343,22 @@ impl Stack {
         display
     }
 
-    /// json representation of the stack.
-    pub fn json(&self, pretty: bool) -> String {
-        // We will actually create a dedicated struct for this... and use serde to serialize it.
-        let mut branches = Vec::new();
-        for branch in self.branches.iter() {
-            branches.push(BranchDisplay {
-                name: branch.name.clone(),
-                status: branch.status.to_string(),
-                commit: branch
-                    .commit_hash
-                    .clone()
-                    .get(..7)
-                    .unwrap_or(&branch.commit_hash)
-                    .to_string(),
-            });
-        }
-
-        let stack = StackDisplay {
-            stack: self.name.clone(),
-            branches,
-        };
-
-        if pretty {
-            serde_json::to_string_pretty(&stack).unwrap()
-        } else {
-            serde_json::to_string(&stack).unwrap()
-        }
+    /// json representation of the stack, following the versioned schema in [`schema`].
+    pub fn json(&self, current_branch: &str, pretty: bool) -> Result<String> {
+        let stack = schema::StackJson::new(self, Some(current_branch));
+        schema::document("stack", &stack, pretty)
     }
 
     /// displays a simple representation of the stack.
     pub fn simple_display(&self) -> String {
-        // We will loop through each branch, and add it to the display, with a ➜ between each.
+        // We will loop through each branch, and add it to the display, with an arrow between each.
+        let arrow = ui::current().glyphs.arrow;
         let mut display = String::new();
-        for (i, branch) in self.branches.iter().enumerate() {
+        for (i, branch) in self.tree_order().iter().enumerate() {
             if i == 0 {
                 display.push_str(&branch.name);
             } else {
-                display.push_str(&format!(" ➜ {}", branch.name));
+                display.push_str(&format!(" {} {}", arrow, branch.name));
             }
         }
         display
@@ -218,3 +370,106 @@ impl fmt::Display for BranchStatus {
         write!(f, "{:?}", self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::Theme;
+
+    /// A stack whose branch names do not exist in git, so rendering falls back to recorded data.
+    fn fixture() -> Stack {
+        let mut stack = Stack::new("snap".to_string(), "main".to_string());
+        stack.branches[0].set_commit_hash("1111111aaaa".to_string());
+
+        for (name, parent, hash) in [
+            ("zyra-snap-a", "snap", "2222222bbbb"),
+            ("zyra-snap-b", "zyra-snap-a", "3333333cccc"),
+            ("zyra-snap-c", "snap", "4444444dddd"),
+        ] {
+            let mut branch = StackBranch::new(name.to_string(), hash.to_string());
+            branch.set_parent(parent.to_string());
+            stack.add_branch(branch).unwrap();
+        }
+        stack.branches[3].set_status(BranchStatus::Merged);
+        stack
+    }
+
+    fn render(ui: &Ui, options: DisplayOptions) -> String {
+        fixture().render(ui, "zyra-snap-a", options)
+    }
+
+    #[test]
+    fn test_snapshot_plain_ascii() {
+        let ui = Ui::new(false, Theme::Default, true);
+        assert_eq!(
+            render(&ui, DisplayOptions::default()),
+            "[zyra]  Stack: snap
+   o snap [1111111]
+   |
+   *   zyra-snap-a [2222222]
+   |
+   o     zyra-snap-b [3333333]
+   |
+   o   zyra-snap-c [4444444] Merged
+"
+        );
+        assert_eq!(
+            render(&ui, DisplayOptions { graph: true, ..Default::default() }),
+            "[zyra]  Stack: snap
+o `--snap [1111111]  (root)
+*     |--zyra-snap-a [2222222] 
+o     |   `--zyra-snap-b [3333333] 
+o     `--zyra-snap-c [4444444]  Merged
+"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_plain_unicode() {
+        let ui = Ui::new(false, Theme::Default, false);
+        assert_eq!(
+            render(&ui, DisplayOptions { reverse: true, ..Default::default() }),
+            "[zyra]  Stack: snap
+   ○   zyra-snap-c [4444444] Merged
+   │
+   ○     zyra-snap-b [3333333]
+   │
+   ●   zyra-snap-a [2222222]
+   │
+   ○ snap [1111111]
+"
+        );
+        assert_eq!(
+            render(&ui, DisplayOptions { graph: true, ..Default::default() }),
+            "[zyra]  Stack: snap
+○ └──snap [1111111]  (root)
+●     ├──zyra-snap-a [2222222] 
+○     │   └──zyra-snap-b [3333333] 
+○     └──zyra-snap-c [4444444]  Merged
+"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_color_themes() {
+        let options = DisplayOptions { depth: Some(1), ..Default::default() };
+
+        let ui = Ui::new(true, Theme::Default, false);
+        assert_eq!(
+            render(&ui, options),
+            "\x1b[95m[zyra]\x1b[0m  \x1b[1mStack: snap\x1b[0m\n   \x1b[2m○\x1b[0m \x1b[1;33msnap\x1b[0m \x1b[34m[1111111]\x1b[0m\n   │\n   \x1b[1;92m●\x1b[0m   \x1b[1;33mzyra-snap-a\x1b[0m \x1b[34m[2222222]\x1b[0m \x1b[2m(+1 collapsed)\x1b[0m\n   │\n   \x1b[2m○\x1b[0m   zyra-snap-c \x1b[34m[4444444]\x1b[0m \x1b[32mMerged\x1b[0m\n"
+        );
+
+        let ui = Ui::new(true, Theme::Mono, false);
+        assert_eq!(
+            render(&ui, options),
+            "\x1b[1m[zyra]\x1b[0m  \x1b[1mStack: snap\x1b[0m\n   \x1b[2m○\x1b[0m \x1b[1msnap\x1b[0m [1111111]\n   │\n   \x1b[1m●\x1b[0m   \x1b[1mzyra-snap-a\x1b[0m [2222222] \x1b[2m(+1 collapsed)\x1b[0m\n   │\n   \x1b[2m○\x1b[0m   zyra-snap-c [4444444] Merged\n"
+        );
+
+        let ui = Ui::new(true, Theme::HighContrast, false);
+        assert_eq!(
+            render(&ui, options),
+            "\x1b[1;95m[zyra]\x1b[0m  \x1b[1;97mStack: snap\x1b[0m\n   \x1b[37m○\x1b[0m \x1b[1;93msnap\x1b[0m \x1b[96m[1111111]\x1b[0m\n   │\n   \x1b[1;92m●\x1b[0m   \x1b[1;93mzyra-snap-a\x1b[0m \x1b[96m[2222222]\x1b[0m \x1b[37m(+1 collapsed)\x1b[0m\n   │\n   \x1b[37m○\x1b[0m   \x1b[97mzyra-snap-c\x1b[0m \x1b[96m[4444444]\x1b[0m \x1b[92mMerged\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use super::restack::RestackState;
+use super::storage::git_dir_path;
+use super::SolMetadata;
+use crate::git;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+/// Most entries kept; older ones are dropped.
+const MAX_ENTRIES: usize = 100;
+
+/// The repository as zyra sees it: branch tips, the checkout and the metadata.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snapshot {
+    /// Tip of each local branch; entries only keep the branches the operation changed, with
+    /// `None` for a branch that did not exist.
+    pub refs: BTreeMap<String, Option<String>>,
+    /// Checked-out branch, or commit hash when HEAD was detached.
+    pub head: String,
+    pub metadata: SolMetadata,
+}
+
+/// One recorded operation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    pub id: u64,
+    pub operation: String,
+    pub created_at: DateTime<Utc>,
+    pub before: Snapshot,
+    pub after: Snapshot,
+}
+
+/// An operation stopped on conflicts; it is recorded once `zyra continue` finishes it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pending {
+    pub operation: String,
+    pub before: Snapshot,
+}
+
+/// Mutating operations, oldest first, persisted so they can be undone and redone.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Journal {
+    pub entries: Vec<Entry>,
+    /// Number of entries in effect; the ones after it were undone and can be redone.
+    pub position: usize,
+    #[serde(default)]
+    pub pending: Option<Pending>,
+}
+
+impl Snapshot {
+    /// capture records every local branch, the checkout and the metadata.
+    pub fn capture() -> Result<Self> {
+        Ok(Snapshot {
+            refs: git::branch::tips()?
+                .into_iter()
+                .map(|(name, hash)| (name, Some(hash)))
+                .collect(),
+            head: git::branch::head()?,
+            metadata: SolMetadata::load()?,
+        })
+    }
+
+    fn same_metadata(&self, other: &Snapshot) -> bool {
+        serde_json::to_value(&self.metadata).ok() == serde_json::to_value(&other.metadata).ok()
+    }
+}
+
+impl Journal {
+    /// load returns the journal, or an empty one.
+    pub fn load() -> Result<Self> {
+        let path = git_dir_path("zyra-journal.json")?;
+        if !path.exists() {
+            return Ok(Journal::default());
+        }
+        let data = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// save writes the journal to disk.
+    pub fn save(&self) -> Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        fs::write(git_dir_path("zyra-journal.json")?, data)?;
+        Ok(())
+    }
+
+    /// record adds an entry for an operation that started from `before`, if it changed anything.
+    pub fn record(operation: &str, before: Snapshot) -> Result<()> {
+        let mut journal = Journal::load()?;
+
+        // Wait for a stopped restack to be continued, keeping the state from before it started.
+        if RestackState::exists() {
+            if journal.pending.is_none() {
+                journal.pending = Some(Pending {
+                    operation: operation.to_string(),
+                    before,
+                });
+            }
+            return journal.save();
+        }
+
+        // An aborted operation put everything back itself.
+        let pending = journal.pending.take();
+        if operation == "abort" {
+            return journal.save();
+        }
+        let (operation, mut before) = match pending {
+            Some(pending) => (pending.operation, pending.before),
+            None => (operation.to_string(), before),
+        };
+
+        let mut after = Snapshot::capture()?;
+        let names: Vec<String> = before.refs.keys().chain(after.refs.keys()).cloned().collect();
+        for name in names {
+            let old = before.refs.get(&name).cloned().flatten();
+            let new = after.refs.get(&name).cloned().flatten();
+            if old == new {
+                before.refs.remove(&name);
+                after.refs.remove(&name);
+            } else {
+                before.refs.insert(name.clone(), old);
+                after.refs.insert(name, new);
+            }
+        }
+
+        if before.refs.is_empty() && before.head == after.head && before.same_metadata(&after) {
+            return journal.save();
+        }
+
+        // A new operation replaces whatever was undone.
+        journal.entries.truncate(journal.position);
+        let id = journal.entries.last().map_or(1, |entry| entry.id + 1);
+        journal.entries.push(Entry {
+            id,
+            operation,
+            created_at: Utc::now(),
+            before,
+            after,
+        });
+        if journal.entries.len() > MAX_ENTRIES {
+            journal.entries.remove(0);
+        }
+        journal.position = journal.entries.len();
+        journal.save()
+    }
+}
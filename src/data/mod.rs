@@ -2,9 +2,14 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
 
+use crate::git;
+
 // Exporting the other parts of the data system.
 pub mod display;
+pub mod journal;
 pub mod operations;
+pub mod restack;
+pub mod schema;
 pub mod storage;
 
 /// Represents a complete stack
@@ -16,6 +21,8 @@ pub struct Stack {
     pub branches: Vec<StackBranch>,   // Ordered list of branches in th stack
     created_at: DateTime<Utc>,    // Timestamp for creation
     updated_at: DateTime<Utc>,    // Last update timestamp
+    #[serde(default)]
+    archived: bool,               // Hidden from default views until restored
 }
 
 /// Reprensents an individual branch in a stack.
@@ -26,6 +33,10 @@ pub struct StackBranch {
     pr_id: Option<u32>,       // Optional PR ID from remote
     pub status: BranchStatus, // Enum: { Pending, Merged, Conflict, Testing }
     pub parent: Option<String>,   // Name of the parent branch, if any
+    #[serde(default)]
+    parent_hash: Option<String>,  // Parent tip this branch was last based on
+    #[serde(default)]
+    remote_hash: Option<String>,  // Commit last pushed to the remote
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     pub depth: u8,
@@ -76,4 +87,21 @@ impl SolMetadata {
     pub fn is_in_detached_head(&self) -> bool {
         self.detached_head_context.is_some()
     }
+
+    /// Checks that the detached-HEAD context still describes the checkout: HEAD is detached on a
+    /// commit of a branch that is still tracked in the recorded stack.
+    pub fn detached_head_context_is_valid(&self) -> bool {
+        let Some(context) = &self.detached_head_context else {
+            return true;
+        };
+        let tracked = self
+            .stacks
+            .iter()
+            .any(|stack| stack.name == context.stack_name && stack.has_branch(&context.branch_name));
+
+        tracked
+            && git::branch::get_branch_name().is_ok_and(|name| name == "HEAD")
+            && git::branch::exists(&context.branch_name)
+            && git::commit::is_ancestor("HEAD", &context.branch_name).unwrap_or(false)
+    }
 }
@@ -5,8 +5,9 @@ use super::*;
 
 impl Stack {
     pub fn new(name: String, base_branch: String) -> Self {
-        // We will first create a new branch for the stack.
-        let mut branch = StackBranch::new(name.clone(), base_branch.clone());
+        // We will first create a new branch for the stack, starting at the base branch's tip.
+        let commit_hash = git::commit::resolve(&base_branch).unwrap_or_default();
+        let mut branch = StackBranch::new(name.clone(), commit_hash);
         branch.depth = 0; // Explicitly set depth to 0 for root branch
         Stack {
             name,
@@ -15,9 +16,21 @@ impl Stack {
             branches: vec![branch],
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            archived: false,
         }
     }
 
+    /// Checks whether the stack has been archived.
+    pub fn is_archived(&self) -> bool {
+        self.archived
+    }
+
+    /// Archives or restores the stack.
+    pub fn set_archived(&mut self, archived: bool) {
+        self.archived = archived;
+        self.updated_at = Utc::now();
+    }
+
     /// Add a new branch to the stack
     pub fn add_branch(&mut self, branch: StackBranch) -> Result<()> {
         self.branches.push(branch);
@@ -32,6 +45,14 @@ impl Stack {
         .ok_or_else(|| anyhow!("Branch not found"))
     }
 
+    /// Get a mutable branch by name
+    pub fn get_branch_mut(&mut self, name: &str) -> Result<&mut StackBranch> {
+        self.branches
+            .iter_mut()
+            .find(|branch| branch.name == name)
+            .ok_or_else(|| anyhow!("Branch not found"))
+    }
+
     /// Remove a branch from the stack
     pub fn remove_branch(&mut self, branch_name: &str) -> Result<()> {
         let index = self
@@ -43,6 +64,52 @@ impl Stack {
         Ok(())
     }
 
+    /// Remove a branch while keeping its children, which move up to the removed branch's parent.
+    pub fn untrack_branch(&mut self, branch_name: &str) -> Result<StackBranch> {
+        let removed = self.get_branch(branch_name)?.clone();
+        for branch in self.branches.iter_mut() {
+            if branch.parent.as_deref() == Some(branch_name) {
+                branch.parent = removed.parent.clone();
+            }
+        }
+        self.remove_branch(branch_name)?;
+        self.refresh();
+        Ok(removed)
+    }
+
+    /// Rename a branch, updating its children's parent links. A stack named after the branch
+    /// takes the new name too.
+    pub fn rename_branch(&mut self, old: &str, new: &str) -> Result<()> {
+        self.get_branch_mut(old)?.name = new.to_string();
+        for branch in self.branches.iter_mut() {
+            if branch.parent.as_deref() == Some(old) {
+                branch.parent = Some(new.to_string());
+            }
+        }
+        if self.name == old {
+            self.name = new.to_string();
+        }
+        self.refresh();
+        Ok(())
+    }
+
+    /// Recalculates depths and the head branch after the tree has changed.
+    pub fn refresh(&mut self) {
+        let depths: Vec<u8> = self
+            .branches
+            .iter()
+            .map(|branch| self.calculate_branch_depth(&branch.name))
+            .collect();
+        for (branch, depth) in self.branches.iter_mut().zip(depths) {
+            branch.depth = depth;
+        }
+
+        if let Some(head) = self.branches.iter().find(|branch| branch.parent.is_none()) {
+            self.head_branch = head.clone();
+        }
+        self.updated_at = Utc::now();
+    }
+
     /// Get children branches of a branch
     pub fn get_children(&self, branch_name: &str) -> Result<Vec<&StackBranch>> {
         let mut children = self
@@ -52,22 +119,98 @@ impl Stack {
             .collect::<Vec<&StackBranch>>();
         
         // Sort children by creation date
-        children.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        children.sort_by_key(|branch| branch.created_at);
         
         Ok(children)
     }
 
+    /// Get the branches in tree order: each branch followed by its descendants, oldest children first.
+    pub fn tree_order(&self) -> Vec<&StackBranch> {
+        fn visit<'a>(stack: &'a Stack, branch: &'a StackBranch, order: &mut Vec<&'a StackBranch>) {
+            order.push(branch);
+            for child in stack.get_children(&branch.name).unwrap_or_default() {
+                visit(stack, child, order);
+            }
+        }
+
+        let mut order = Vec::new();
+        for root in self
+            .branches
+            .iter()
+            .filter(|b| b.parent.as_deref().is_none_or(|p| !self.has_branch(p)))
+        {
+            visit(self, root, &mut order);
+        }
+        order
+    }
+
+    /// Get every branch below a branch, in tree order.
+    pub fn descendants(&self, branch_name: &str) -> Vec<&StackBranch> {
+        let mut descendants = Vec::new();
+        for child in self.get_children(branch_name).unwrap_or_default() {
+            descendants.push(child);
+            descendants.extend(self.descendants(&child.name));
+        }
+        descendants
+    }
+
+    /// Get a branch and every branch it is built on, from the stack's root up to the branch.
+    pub fn ancestry(&self, branch_name: &str) -> Result<Vec<&StackBranch>> {
+        let mut ancestry = vec![self.get_branch(branch_name)?];
+        while let Some(parent) = ancestry.last().and_then(|branch| branch.parent.as_deref()) {
+            match self.branches.iter().find(|branch| branch.name == parent) {
+                Some(branch) if !ancestry.iter().any(|b| b.name == branch.name) => ancestry.push(branch),
+                _ => break,
+            }
+        }
+        ancestry.reverse();
+        Ok(ancestry)
+    }
+
+    /// Count every branch below a branch.
+    pub fn count_descendants(&self, branch_name: &str) -> usize {
+        self.get_children(branch_name)
+            .unwrap_or_default()
+            .iter()
+            .map(|child| 1 + self.count_descendants(&child.name))
+            .sum()
+    }
+
     /// Checking if a branch exists.
     pub fn has_branch(&self, name: &str) -> bool {
         self.branches.iter().any(|branch| branch.name == name)
     }
 
-    /// Calculate the depth of a branch by traversing its parent chain
+    /// Get the name of the stack's base branch.
+    pub fn base_branch(&self) -> &str {
+        &self.base_branch
+    }
+
+    /// Get the name of the branch a branch is based on; the root is based on the stack's base branch.
+    pub fn parent_of<'a>(&'a self, branch: &'a StackBranch) -> &'a str {
+        branch.parent.as_deref().unwrap_or(&self.base_branch)
+    }
+
+    /// Checks whether a branch's parent has moved since the branch was last rebased onto it.
+    pub fn needs_restack(&self, branch: &StackBranch) -> bool {
+        matches!(
+            git::commit::is_ancestor(self.parent_of(branch), &branch.name),
+            Ok(false)
+        )
+    }
+
+    /// Calculate the depth of a branch by traversing its parent chain. A chain that loops, which
+    /// `zyra doctor` reports, stops once it has visited as many branches as the stack has.
     pub fn calculate_branch_depth(&self, branch_name: &str) -> u8 {
-        let mut depth = 0;
+        let mut depth: u8 = 0;
         let mut current_name = branch_name;
 
-        while let Some(branch) = self.branches.iter().find(|b| b.name == current_name) {
+        while let Some(branch) = self
+            .branches
+            .iter()
+            .find(|b| b.name == current_name)
+            .filter(|_| (depth as usize) < self.branches.len())
+        {
             if let Some(parent_name) = &branch.parent {
                 depth += 1;
                 current_name = parent_name;
@@ -88,6 +231,8 @@ impl StackBranch {
             pr_id: None,
             status: BranchStatus::Pending,
             parent: None,
+            parent_hash: None,
+            remote_hash: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
             depth: 0,
@@ -102,6 +247,11 @@ impl StackBranch {
         self.pr_id = Some(pr_id);
     }
 
+    /// The pull request opened for this branch, if any.
+    pub fn pr_id(&self) -> Option<u32> {
+        self.pr_id
+    }
+
     pub fn set_parent(&mut self, parent: String) {
         self.parent = Some(parent);
     }
@@ -110,6 +260,29 @@ impl StackBranch {
         self.commit_hash = commit_hash;
         self.updated_at = Utc::now();
     }
+
+    /// The commit this branch pointed at when zyra last updated it.
+    pub fn commit_hash(&self) -> &str {
+        &self.commit_hash
+    }
+
+    /// The parent tip this branch was last based on, if recorded.
+    pub fn parent_hash(&self) -> Option<&str> {
+        self.parent_hash.as_deref()
+    }
+
+    pub fn set_parent_hash(&mut self, parent_hash: String) {
+        self.parent_hash = Some(parent_hash);
+    }
+
+    /// The commit zyra last pushed for this branch, if it has pushed it.
+    pub fn remote_hash(&self) -> Option<&str> {
+        self.remote_hash.as_deref()
+    }
+
+    pub fn set_remote_hash(&mut self, remote_hash: String) {
+        self.remote_hash = Some(remote_hash);
+    }
 }
 
 impl SolMetadata {
@@ -129,9 +302,31 @@ impl SolMetadata {
         self.stacks.iter().any(|stack| stack.has_branch(name))
     }
 
+    /// Gets the branch zyra treats as checked out: the branch itself, or while HEAD is detached
+    /// on one of its commits, the branch recorded in the detached-HEAD context.
+    pub fn current_branch(&self) -> Result<String> {
+        let name = git::branch::get_branch_name()?;
+        match &self.detached_head_context {
+            Some(context) if name == "HEAD" => Ok(context.branch_name.clone()),
+            _ => Ok(name),
+        }
+    }
+
+    /// Gets the checked-out branch for commands that rewrite it, refusing while HEAD is detached.
+    pub fn checked_out_branch(&self) -> Result<String> {
+        if let Some(context) = &self.detached_head_context {
+            return Err(anyhow!(
+                "HEAD is detached at a commit of '{}'. Run `zyra goto {}` to return to the branch first.",
+                context.branch_name,
+                context.branch_name
+            ));
+        }
+        git::branch::get_branch_name()
+    }
+
     /// Gets the current stack based on the current branch.
     pub fn get_current_stack(&self) -> Result<&Stack> {
-        let branch_name = git::branch::get_branch_name()?;
+        let branch_name = self.current_branch()?;
         let stack = self
             .stacks
             .iter()
@@ -142,7 +337,7 @@ impl SolMetadata {
 
     /// Gets a mutable reference to the current stack based on the current branch.
     pub fn get_current_stack_mut(&mut self) -> Result<&mut Stack> {
-        let branch_name = git::branch::get_branch_name()?;
+        let branch_name = self.current_branch()?;
         let stack = self
             .stacks
             .iter_mut()
@@ -151,6 +346,34 @@ impl SolMetadata {
         Ok(stack)
     }
 
+    /// Get the stack that tracks a branch, if any
+    pub fn find_stack(&self, branch_name: &str) -> Option<&Stack> {
+        self.stacks.iter().find(|stack| stack.has_branch(branch_name))
+    }
+
+    /// Get a mutable reference to the stack that tracks a branch, if any
+    pub fn find_stack_mut(&mut self, branch_name: &str) -> Option<&mut Stack> {
+        self.stacks.iter_mut().find(|stack| stack.has_branch(branch_name))
+    }
+
+    /// Get a mutable stack by name
+    pub fn get_stack_mut(&mut self, name: &str) -> Result<&mut Stack> {
+        self.stacks
+            .iter_mut()
+            .find(|stack| stack.name == name)
+            .ok_or_else(|| anyhow!("Stack not found"))
+    }
+
+    /// Remove a stack from the metadata
+    pub fn remove_stack(&mut self, name: &str) -> Result<Stack> {
+        let index = self
+            .stacks
+            .iter()
+            .position(|stack| stack.name == name)
+            .ok_or_else(|| anyhow!("Stack not found"))?;
+        Ok(self.stacks.remove(index))
+    }
+
     /// Get stack by name
     pub fn get_stack(&self, name: &str) -> Result<&Stack> {
         self.stacks
@@ -0,0 +1,93 @@
+use super::storage::git_dir_path;
+use super::SolMetadata;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+/// A restack that is running or stopped on conflicts, persisted so `zyra continue` and
+/// `zyra abort` can pick it up.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RestackState {
+    /// The command that started the restack, e.g. "modify".
+    pub operation: String,
+    /// Branch to check out once the restack is done or aborted.
+    pub original_branch: String,
+    /// Branches still to rebase onto their parents, in order.
+    pub pending: Vec<String>,
+    /// Branch whose rebase stopped on conflicts, if any. It is also the first pending branch.
+    pub in_progress: Option<String>,
+    /// Tip of every branch touched so far, as it was before the operation.
+    pub old_tips: BTreeMap<String, String>,
+    /// Branch whose new commit is put back into the index on abort.
+    #[serde(default)]
+    pub staged_branch: Option<String>,
+    /// Changes to put back into the index once the restack is done.
+    #[serde(default)]
+    pub finish_patch: Option<String>,
+    /// Changes to put back into the index if the restack is aborted.
+    #[serde(default)]
+    pub abort_patch: Option<String>,
+    /// Commit after which a branch's own commits start, for branches that moved to a new parent.
+    #[serde(default)]
+    pub bases: BTreeMap<String, String>,
+    /// Metadata as it was before the operation, put back if the restack is aborted.
+    #[serde(default)]
+    pub snapshot: Option<SolMetadata>,
+    /// Pull request changes of a fold, made on the forge once the restack is done.
+    #[serde(default)]
+    pub fold: Option<FoldPullRequests>,
+}
+
+/// What a fold changes on the forge: the removed branch's pull request is closed, and those of
+/// the branches that sat on it are pointed at their new parents.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FoldPullRequests {
+    pub removed: String,
+    pub kept: String,
+    pub removed_pr: Option<u32>,
+    /// Branch, pull request number and new base of each pull request to retarget.
+    pub retargets: Vec<(String, u32, String)>,
+}
+
+impl RestackState {
+    pub fn new(operation: &str, original_branch: &str, pending: Vec<String>) -> Self {
+        RestackState {
+            operation: operation.to_string(),
+            original_branch: original_branch.to_string(),
+            pending,
+            ..Default::default()
+        }
+    }
+
+    /// load returns the saved restack, if one is in progress.
+    pub fn load() -> Result<Option<Self>> {
+        let path = git_dir_path("zyra-restack.json")?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    /// save writes the restack to disk so it can be resumed.
+    pub fn save(&self) -> Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        fs::write(git_dir_path("zyra-restack.json")?, data)?;
+        Ok(())
+    }
+
+    /// clear removes the saved restack.
+    pub fn clear() -> Result<()> {
+        let path = git_dir_path("zyra-restack.json")?;
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// exists checks whether a restack is in progress.
+    pub fn exists() -> bool {
+        git_dir_path("zyra-restack.json").is_ok_and(|path| path.exists())
+    }
+}
@@ -0,0 +1,168 @@
+//! Versioned JSON schema shared by every `--json` output.
+//!
+//! Every document is an object carrying `schema_version` next to a single payload
+//! key (for example `stack`). Fields are only ever added within a version; renaming
+//! or removing a field bumps [`SCHEMA_VERSION`].
+use super::{Stack, StackBranch};
+use crate::git;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Current version of the JSON schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON representation of a stack.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StackJson {
+    pub name: String,
+    pub base_branch: String,
+    pub head_branch: String,
+    /// The branch HEAD is on, if it belongs to this stack.
+    pub current_branch: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Branches in stack order, roots first.
+    pub branches: Vec<BranchJson>,
+}
+
+/// JSON representation of a branch within a stack.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BranchJson {
+    pub name: String,
+    /// Parent branch name; `null` for the root, whose parent is the stack's base branch.
+    pub parent: Option<String>,
+    pub children: Vec<String>,
+    pub depth: u8,
+    pub status: String,
+    /// Full hash of the branch tip. Falls back to the recorded hash when the branch is missing.
+    pub commit: String,
+    pub is_current: bool,
+    /// Commits on this branch that its parent does not have.
+    pub ahead: Option<u32>,
+    /// Commits on the parent that this branch does not have.
+    pub behind: Option<u32>,
+    pub upstream: Option<UpstreamJson>,
+    /// Whether the parent tip has moved since this branch was last rebased onto it.
+    pub needs_restack: bool,
+    pub pr: Option<PullRequestJson>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Remote tracking information for a branch.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpstreamJson {
+    pub name: String,
+    pub ahead: Option<u32>,
+    pub behind: Option<u32>,
+}
+
+/// Pull request information for a branch.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PullRequestJson {
+    pub number: u32,
+}
+
+impl StackJson {
+    /// Builds the JSON representation of a stack, querying git for live branch data.
+    pub fn new(stack: &Stack, current_branch: Option<&str>) -> Self {
+        let current_branch = current_branch.filter(|name| stack.has_branch(name));
+
+        StackJson {
+            name: stack.name.clone(),
+            base_branch: stack.base_branch.clone(),
+            head_branch: stack.head_branch.name.clone(),
+            current_branch: current_branch.map(str::to_string),
+            created_at: stack.created_at,
+            updated_at: stack.updated_at,
+            branches: stack
+                .branches
+                .iter()
+                .map(|branch| BranchJson::new(stack, branch, current_branch))
+                .collect(),
+        }
+    }
+}
+
+impl BranchJson {
+    fn new(stack: &Stack, branch: &StackBranch, current_branch: Option<&str>) -> Self {
+        let parent = stack.parent_of(branch);
+        let (ahead, behind) = match git::commit::ahead_behind(parent, &branch.name) {
+            Ok((ahead, behind)) => (Some(ahead), Some(behind)),
+            Err(_) => (None, None),
+        };
+
+        let upstream = git::branch::get_upstream(&branch.name).map(|name| {
+            let counts = git::commit::ahead_behind(&name, &branch.name).ok();
+            UpstreamJson {
+                ahead: counts.map(|(ahead, _)| ahead),
+                behind: counts.map(|(_, behind)| behind),
+                name,
+            }
+        });
+
+        BranchJson {
+            name: branch.name.clone(),
+            parent: branch.parent.clone(),
+            children: stack
+                .get_children(&branch.name)
+                .map(|children| children.iter().map(|c| c.name.clone()).collect())
+                .unwrap_or_default(),
+            depth: branch.depth,
+            status: branch.status.to_string(),
+            commit: git::commit::resolve(&branch.name).unwrap_or_else(|_| branch.commit_hash.clone()),
+            is_current: current_branch == Some(branch.name.as_str()),
+            ahead,
+            behind,
+            upstream,
+            needs_restack: stack.needs_restack(branch),
+            pr: branch.pr_id.map(|number| PullRequestJson { number }),
+            created_at: branch.created_at,
+            updated_at: branch.updated_at,
+        }
+    }
+}
+
+/// Serializes `payload` under `key` in a versioned JSON document.
+pub fn document<T: Serialize>(key: &str, payload: &T, pretty: bool) -> Result<String> {
+    let mut doc = serde_json::Map::new();
+    doc.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+    doc.insert(key.to_string(), serde_json::to_value(payload)?);
+    let doc = serde_json::Value::Object(doc);
+
+    Ok(if pretty {
+        serde_json::to_string_pretty(&doc)?
+    } else {
+        serde_json::to_string(&doc)?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_document_includes_links() {
+        let mut stack = Stack::new("zyra-schema-root".to_string(), "main".to_string());
+        let mut child = StackBranch::new("zyra-schema-child".to_string(), "abc123".to_string());
+        child.set_parent("zyra-schema-root".to_string());
+        child.depth = 1;
+        child.set_pr_id(42);
+        stack.add_branch(child).unwrap();
+
+        let json = document("stack", &StackJson::new(&stack, Some("zyra-schema-child")), false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["stack"]["base_branch"], "main");
+        assert_eq!(value["stack"]["current_branch"], "zyra-schema-child");
+
+        let branches = value["stack"]["branches"].as_array().unwrap();
+        assert_eq!(branches[0]["children"][0], "zyra-schema-child");
+        assert_eq!(branches[1]["parent"], "zyra-schema-root");
+        assert_eq!(branches[1]["commit"], "abc123");
+        assert_eq!(branches[1]["pr"]["number"], 42);
+        assert_eq!(branches[1]["is_current"], true);
+    }
+}
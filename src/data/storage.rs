@@ -11,8 +11,19 @@ impl SolMetadata {
         Ok(())
     }
 
-    /// load the database file from disk.
+    /// load the database file from disk. A detached-HEAD context left behind by a plain
+    /// `git switch` or a new commit no longer applies, so it is dropped; commands that save
+    /// persist that.
     pub fn load() -> Result<Self> {
+        let mut metadata = Self::load_stored()?;
+        if !metadata.detached_head_context_is_valid() {
+            metadata.clear_detached_head_context();
+        }
+        Ok(metadata)
+    }
+
+    /// load_stored loads the database file exactly as it was saved.
+    pub fn load_stored() -> Result<Self> {
         let path = Self::get_storage_path()?;
         if !path.exists() {
             return Ok(SolMetadata {
@@ -22,25 +33,29 @@ impl SolMetadata {
             });
         }
         let data = fs::read_to_string(path)?;
-        let metadata: SolMetadata = serde_json::from_str(&data)?;
-        Ok(metadata)
+        Ok(serde_json::from_str(&data)?)
     }
 
     /// get_storage_path returns the path to the database file.
     fn get_storage_path() -> Result<PathBuf> {
-        // Get the git directory using git rev-parse
-        let output = Command::new("git")
-            .arg("rev-parse")
-            .arg("--git-dir")
-            .output()?;
+        git_dir_path("zyra-metadata.json")
+    }
+}
 
-        if !output.status.success() {
-            return Err(anyhow!("Not in a git repository. Please run this command from within a git repository."));
-        }
+/// git_dir_path returns the path of a file zyra keeps inside the git directory.
+pub(crate) fn git_dir_path(file_name: &str) -> Result<PathBuf> {
+    // Get the git directory using git rev-parse
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--git-dir")
+        .output()?;
 
-        let git_dir = String::from_utf8(output.stdout)?.trim().to_string();
-        let mut path = PathBuf::from(git_dir);
-        path.push("zyra-metadata.json");
-        Ok(path)
+    if !output.status.success() {
+        return Err(anyhow!("Not in a git repository. Please run this command from within a git repository."));
     }
+
+    let git_dir = String::from_utf8(output.stdout)?.trim().to_string();
+    let mut path = PathBuf::from(git_dir);
+    path.push(file_name);
+    Ok(path)
 }
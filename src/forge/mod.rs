@@ -0,0 +1,170 @@
+//! Pull requests on the code hosting service named by the `forge.*` settings.
+//!
+//! Only GitHub is supported. The repository is taken from the remote's URL, the API host from
+//! `forge.host` (`api.github.com` by default; a full URL is used as is) and the token from the
+//! environment variable named by `forge.token_env` (`GITHUB_TOKEN` by default).
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::git;
+
+/// A pull request, as far as zyra cares.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PullRequest {
+    pub number: u32,
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    pub state: String,
+}
+
+/// Fields sent when opening a pull request.
+#[derive(Serialize)]
+struct NewPullRequest<'a> {
+    title: &'a str,
+    body: Option<&'a str>,
+    head: &'a str,
+    base: &'a str,
+    draft: bool,
+}
+
+/// A GitHub repository reached through its REST API.
+pub struct Forge {
+    client: reqwest::Client,
+    api: String,
+    repo: String,
+    token: String,
+}
+
+impl Forge {
+    /// Connects to the forge hosting `remote`, or returns `None` when no forge is configured.
+    pub fn connect(config: &Config, remote: &str) -> Result<Option<Self>> {
+        let Some(provider) = config.forge.provider.as_deref() else {
+            return Ok(None);
+        };
+        if provider != "github" {
+            return Err(anyhow!("Unsupported forge '{}'.", provider));
+        }
+
+        let token_env = config.forge.token_env.as_deref().unwrap_or("GITHUB_TOKEN");
+        let token = std::env::var(token_env)
+            .map_err(|_| anyhow!("Set {} to a token for the forge API.", token_env))?;
+        let host = config.forge.host.as_deref().unwrap_or("api.github.com");
+        let api = if host.starts_with("http://") || host.starts_with("https://") {
+            host.trim_end_matches('/').to_string()
+        } else {
+            format!("https://{}", host.trim_end_matches('/'))
+        };
+        let url = git::remote::url(remote)?;
+        let repo = repository(&url).ok_or_else(|| anyhow!("Cannot tell the repository from '{}'.", url))?;
+
+        Ok(Some(Forge::new(&api, &repo, &token)))
+    }
+
+    /// A forge reached at the API URL `api`, for the `owner/name` repository.
+    pub fn new(api: &str, repo: &str, token: &str) -> Self {
+        Forge {
+            client: reqwest::Client::new(),
+            api: api.to_string(),
+            repo: repo.to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    pub async fn pull_request(&self, number: u32) -> Result<PullRequest> {
+        self.send(self.request(reqwest::Method::GET, &format!("pulls/{}", number))).await
+    }
+
+    /// Opens a pull request and returns its number.
+    pub async fn open(&self, head: &str, base: &str, title: &str, body: Option<&str>, draft: bool) -> Result<u32> {
+        let request = self
+            .request(reqwest::Method::POST, "pulls")
+            .json(&NewPullRequest { title, body, head, base, draft });
+        Ok(self.send::<PullRequest>(request).await?.number)
+    }
+
+    /// Points a pull request at a new base branch.
+    pub async fn set_base(&self, number: u32, base: &str) -> Result<()> {
+        let request = self
+            .request(reqwest::Method::PATCH, &format!("pulls/{}", number))
+            .json(&serde_json::json!({ "base": base }));
+        self.send::<PullRequest>(request).await?;
+        Ok(())
+    }
+
+    /// Closes a pull request, leaving a comment that says why.
+    pub async fn close(&self, number: u32, reason: &str) -> Result<()> {
+        let request = self
+            .request(reqwest::Method::POST, &format!("issues/{}/comments", number))
+            .json(&serde_json::json!({ "body": reason }));
+        self.send::<serde_json::Value>(request).await?;
+
+        let request = self
+            .request(reqwest::Method::PATCH, &format!("pulls/{}", number))
+            .json(&serde_json::json!({ "state": "closed" }));
+        self.send::<PullRequest>(request).await?;
+        Ok(())
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, format!("{}/repos/{}/{}", self.api, self.repo, path))
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header(reqwest::header::USER_AGENT, "zyra")
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("The forge answered {}: {}", status, body.trim()));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+/// `owner/name` from a remote URL in any of the forms git accepts.
+fn repository(url: &str) -> Option<String> {
+    let path = url.trim_end_matches('/').trim_end_matches(".git");
+    let path = match path.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => path.rsplit_once(':').map_or(path, |(_, path)| path),
+    };
+    let mut parts = path.rsplit('/').filter(|part| !part.is_empty());
+    let name = parts.next()?;
+    let owner = parts.next()?;
+    Some(format!("{}/{}", owner, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository() {
+        for url in [
+            "git@github.com:acme/widgets.git",
+            "https://github.com/acme/widgets",
+            "https://github.com/acme/widgets.git/",
+            "ssh://git@github.example.com:2222/acme/widgets.git",
+        ] {
+            assert_eq!(repository(url).as_deref(), Some("acme/widgets"), "{}", url);
+        }
+        assert_eq!(repository("widgets"), None);
+    }
+
+    #[test]
+    fn test_connect_needs_a_supported_provider() {
+        let mut config = Config::default();
+        assert!(Forge::connect(&config, "origin").unwrap().is_none());
+
+        config.forge.provider = Some("gitlab".to_string());
+        let err = Forge::connect(&config, "origin").err().unwrap();
+        assert_eq!(err.to_string(), "Unsupported forge 'gitlab'.");
+    }
+}
@@ -1,5 +1,7 @@
 use anyhow::{anyhow, Result};
-use std::process::Command;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 /// get_branch_name returns the current branch name.
 pub fn get_branch_name() -> Result<String> {
@@ -34,20 +36,146 @@ pub fn switch(name: &str, create: bool) -> Result<()> {
     Ok(())
 }
 
-/// Sets the upstream branch for the current branch.
-pub fn set_upstream(name: &str) -> Result<()> {
-    let output = Command::new("git")
-        .arg("branch")
-        .arg("--set-upstream-to")
-        .arg(name)
-        .output()?;
+/// Creates a branch at `start` and switches to it.
+pub fn create_from(name: &str, start: &str) -> Result<()> {
+    super::run(&["switch", "-c", name, start])?;
+    Ok(())
+}
+
+/// Creates a branch at a commit without switching to it.
+pub fn create_at(name: &str, start: &str) -> Result<()> {
+    super::run(&["branch", name, start])?;
+    Ok(())
+}
+
+/// Renames a local branch, carrying its reflog and configuration along.
+pub fn rename(old: &str, new: &str) -> Result<()> {
+    super::run(&["branch", "--move", old, new])?;
+    Ok(())
+}
+
+/// Deletes a local branch; `force` also deletes unmerged branches.
+pub fn delete(name: &str, force: bool) -> Result<()> {
+    super::run(&["branch", if force { "-D" } else { "-d" }, name])?;
+    Ok(())
+}
+
+/// Points a branch at a commit without checking it out.
+pub fn update_ref(name: &str, commit_hash: &str) -> Result<()> {
+    super::run(&["update-ref", &format!("refs/heads/{}", name), commit_hash])?;
+    Ok(())
+}
+
+/// Moves the current branch to a commit; `soft` keeps the difference staged, otherwise
+/// local changes are kept only if they do not conflict.
+pub fn reset(commit_hash: &str, soft: bool) -> Result<()> {
+    super::run(&["reset", if soft { "--soft" } else { "--keep" }, commit_hash])?;
+    Ok(())
+}
+
+/// Throws away staged and unstaged changes to tracked files.
+pub fn discard_changes() -> Result<()> {
+    super::run(&["reset", "--hard", "--quiet", "HEAD"])?;
+    Ok(())
+}
+
+/// Lists every local branch with the commit it points at.
+pub fn tips() -> Result<BTreeMap<String, String>> {
+    let output = super::run(&["for-each-ref", "--format=%(refname:short) %(objectname)", "refs/heads"])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, hash)| (name.to_string(), hash.to_string()))
+        .collect())
+}
 
+/// Moves, creates (`old` is None) or deletes (`new` is None) local branches in one transaction.
+/// Nothing changes unless every branch still points at its `old` value.
+pub fn update_refs(updates: &[(String, Option<String>, Option<String>)]) -> Result<()> {
+    const MISSING: &str = "0000000000000000000000000000000000000000";
+
+    let mut script = String::from("start\n");
+    for (name, new, old) in updates {
+        script.push_str(&format!(
+            "update refs/heads/{} {} {}\n",
+            name,
+            new.as_deref().unwrap_or(MISSING),
+            old.as_deref().unwrap_or(MISSING)
+        ));
+    }
+    script.push_str("prepare\ncommit\n");
+
+    let mut child = Command::new("git")
+        .args(["update-ref", "--stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Could not write to git update-ref"))?
+        .write_all(script.as_bytes())?;
+
+    let output = child.wait_with_output()?;
     if !output.status.success() {
-        return Err(anyhow!("{}", String::from_utf8(output.stderr)?));
+        return Err(anyhow!("{}", String::from_utf8(output.stderr)?.trim()));
     }
     Ok(())
 }
 
+/// Gets the checked-out branch, or the commit hash when HEAD is detached.
+pub fn head() -> Result<String> {
+    match super::run(&["symbolic-ref", "--quiet", "--short", "HEAD"]) {
+        Ok(name) => Ok(name),
+        Err(_) => super::run(&["rev-parse", "HEAD"]),
+    }
+}
+
+/// Detaches HEAD at the current commit, leaving the working tree as it is.
+pub fn detach() -> Result<()> {
+    super::run(&["checkout", "--quiet", "--detach"])?;
+    Ok(())
+}
+
+/// Checks whether a local branch exists.
+pub fn exists(name: &str) -> bool {
+    super::run(&["show-ref", "--verify", "--quiet", &format!("refs/heads/{}", name)]).is_ok()
+}
+
+/// Checks whether a remote tracking branch exists.
+pub fn remote_exists(remote: &str, name: &str) -> bool {
+    super::run(&[
+        "show-ref",
+        "--verify",
+        "--quiet",
+        &format!("refs/remotes/{}/{}", remote, name),
+    ])
+    .is_ok()
+}
+
+/// Gets the branch the remote's HEAD points at, e.g. `main` for `refs/remotes/origin/HEAD`.
+pub fn remote_default_branch(remote: &str) -> Option<String> {
+    let head = super::run(&["symbolic-ref", "--short", &format!("refs/remotes/{}/HEAD", remote)]).ok()?;
+    head.strip_prefix(&format!("{}/", remote)).map(str::to_string)
+}
+
+/// Creates a local branch tracking the same-named branch on the remote.
+pub fn create_tracking(remote: &str, name: &str) -> Result<()> {
+    super::run(&["branch", "--track", name, &format!("{}/{}", remote, name)])?;
+    Ok(())
+}
+
+/// Makes the same-named branch on a remote the upstream of a local branch.
+pub fn set_upstream(name: &str, remote: &str) -> Result<()> {
+    super::run(&[
+        "branch",
+        &format!("--set-upstream-to={}/{}", remote, name),
+        name,
+    ])?;
+    Ok(())
+}
+
 /// Gets the list of branches known to git.
 pub fn get_branches(local_only: bool) -> Result<Vec<String>> {
     let mut cmd = Command::new("git");
@@ -93,6 +221,18 @@ pub fn get_current_branch() -> Result<String> {
     get_branch_name()
 }
 
+/// Gets the remote tracking branch configured for the given branch, if any.
+pub fn get_upstream(name: &str) -> Option<String> {
+    super::run(&[
+        "rev-parse",
+        "--abbrev-ref",
+        "--symbolic-full-name",
+        &format!("{}@{{upstream}}", name),
+    ])
+    .ok()
+    .filter(|upstream| !upstream.is_empty())
+}
+
 /// Check if a commit hash exists
 pub fn commit_exists(commit_hash: &str) -> Result<bool> {
     let output = Command::new("git")
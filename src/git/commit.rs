@@ -3,21 +3,131 @@ use std::process::Command;
 
 /// get_hash returns the current commit hash.
 pub fn get_hash() -> Result<String> {
-    let output = Command::new("git").arg("rev-parse").arg("HEAD").output()?;
+    super::run(&["rev-parse", "HEAD"])
+}
+
+/// has_staged_changes checks whether the index differs from HEAD.
+pub fn has_staged_changes() -> Result<bool> {
+    let output = Command::new("git")
+        .arg("diff")
+        .arg("--cached")
+        .arg("--quiet")
+        .output()?;
+
+    match output.status.code() {
+        Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        _ => Err(anyhow!("{}", String::from_utf8(output.stderr)?.trim())),
+    }
+}
+
+/// has_unstaged_changes checks whether tracked files in the working tree differ from the index.
+pub fn has_unstaged_changes() -> Result<bool> {
+    let output = Command::new("git").arg("diff").arg("--quiet").output()?;
+
+    match output.status.code() {
+        Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        _ => Err(anyhow!("{}", String::from_utf8(output.stderr)?.trim())),
+    }
+}
+
+/// stage_all stages every change in the working tree, including untracked files.
+pub fn stage_all() -> Result<()> {
+    super::run(&["add", "--all"])?;
+    Ok(())
+}
+
+/// create commits the staged changes with the given message and returns the new hash.
+pub fn create(message: &str) -> Result<String> {
+    super::run(&["commit", "--quiet", "--message", message])?;
+    get_hash()
+}
+
+/// amend folds the staged changes into HEAD, optionally rewording it, and returns the new hash.
+pub fn amend(message: Option<&str>) -> Result<String> {
+    match message {
+        Some(message) => super::run(&["commit", "--quiet", "--amend", "--message", message])?,
+        None => super::run(&["commit", "--quiet", "--amend", "--no-edit"])?,
+    };
+    get_hash()
+}
+
+/// commit_tree creates a commit of `tree` on top of `parent` without touching any branch.
+pub fn commit_tree(tree: &str, parent: &str, message: &str) -> Result<String> {
+    super::run(&["commit-tree", tree, "-p", parent, "-m", message])
+}
 
-    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+/// subject returns the first line of a commit's message.
+pub fn subject(rev: &str) -> Result<String> {
+    super::run(&["log", "-1", "--format=%s", rev])
 }
 
-/// is_commit checks if the given commit hash is valid.
+/// merge_base returns the best common ancestor of two revisions.
+pub fn merge_base(a: &str, b: &str) -> Result<String> {
+    super::run(&["merge-base", a, b])
+}
+
+/// is_commit checks if the given name resolves to a commit; git failing to resolve it means no.
 pub fn is_commit(commit_hash: &str) -> bool {
+    super::run(&["cat-file", "-e", &format!("{}^{{commit}}", commit_hash)]).is_ok()
+}
+
+/// resolve returns the full commit hash that the given revision points at.
+pub fn resolve(rev: &str) -> Result<String> {
+    super::run(&["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)])
+        .map_err(|_| anyhow!("Could not resolve '{}' to a commit", rev))
+}
+
+/// ahead_behind returns how many commits `rev` has that `base` does not, and vice versa.
+pub fn ahead_behind(base: &str, rev: &str) -> Result<(u32, u32)> {
+    let output = super::run(&[
+        "rev-list",
+        "--left-right",
+        "--count",
+        &format!("{}...{}", base, rev),
+    ])?;
+
+    let mut counts = output.split_whitespace().map(str::parse::<u32>);
+    match (counts.next(), counts.next()) {
+        (Some(Ok(behind)), Some(Ok(ahead))) => Ok((ahead, behind)),
+        _ => Err(anyhow!("Unexpected rev-list output: {}", output)),
+    }
+}
+
+/// is_ancestor checks whether `ancestor` is reachable from `rev`.
+pub fn is_ancestor(ancestor: &str, rev: &str) -> Result<bool> {
     let output = Command::new("git")
-        .arg("cat-file")
-        .arg("-e")
-        .arg(commit_hash)
-        .output();
-
-    match output {
-        Ok(_) => true,
-        Err(_) => false,
+        .arg("merge-base")
+        .arg("--is-ancestor")
+        .arg(ancestor)
+        .arg(rev)
+        .output()?;
+
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => Err(anyhow!("{}", String::from_utf8(output.stderr)?.trim())),
     }
-}
\ No newline at end of file
+}
+
+/// A single commit as shown in listings.
+#[derive(Debug, Clone)]
+pub struct CommitSummary {
+    pub hash: String,
+    pub subject: String,
+}
+
+/// list returns the commits reachable from `rev` but not from `base`, newest first.
+pub fn list(base: &str, rev: &str) -> Result<Vec<CommitSummary>> {
+    let output = super::run(&["log", "--format=%H%x09%s", &format!("{}..{}", base, rev)])?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(hash, subject)| CommitSummary {
+            hash: hash.to_string(),
+            subject: subject.to_string(),
+        })
+        .collect())
+}
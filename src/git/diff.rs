@@ -0,0 +1,294 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// One hunk of a unified diff: its `@@` line and body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_count: u32,
+    pub new_start: u32,
+    pub new_count: u32,
+    /// The body lines, each ending in a newline.
+    pub body: String,
+}
+
+/// The diff of a single file: the header lines (`diff --git`, `index`, `---`, `+++`, binary
+/// data, ...) followed by its hunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    pub path: String,
+    pub header: String,
+    pub hunks: Vec<Hunk>,
+}
+
+impl Hunk {
+    /// Renders the hunk with its old and new line numbers moved by the given offsets.
+    pub fn render(&self, old_offset: i64, new_offset: i64) -> String {
+        let shift = |start: u32, offset: i64| (start as i64 + offset).max(0);
+        format!(
+            "@@ -{},{} +{},{} @@\n{}",
+            shift(self.old_start, old_offset),
+            self.old_count,
+            shift(self.new_start, new_offset),
+            self.new_count,
+            self.body
+        )
+    }
+
+    /// The net number of lines the hunk adds.
+    pub fn delta(&self) -> i64 {
+        self.new_count as i64 - self.old_count as i64
+    }
+
+    /// The lines the hunk replaces, or for a pure insertion the line it follows.
+    pub fn old_end(&self) -> u32 {
+        if self.old_count == 0 {
+            self.old_start
+        } else {
+            self.old_start + self.old_count - 1
+        }
+    }
+}
+
+impl FileDiff {
+    /// Checks whether the diff only edits lines of an existing text file, so its hunks can be
+    /// applied one by one.
+    pub fn is_line_edit(&self) -> bool {
+        self.header.lines().any(|line| line.starts_with("--- a/"))
+            && self.header.lines().any(|line| line.starts_with("+++ b/"))
+            && !self.header.lines().any(|line| {
+                ["new file", "deleted file", "rename ", "copy ", "old mode", "GIT binary patch", "Binary files"]
+                    .iter()
+                    .any(|prefix| line.starts_with(prefix))
+            })
+    }
+
+    /// Renders the header and the given hunks, in order, each moved by its offset in the file
+    /// the patch applies to. The new line numbers are worked out from the hunks rendered before,
+    /// since `git apply --unidiff-zero` places insertions by them.
+    pub fn render(&self, hunks: &[(&Hunk, i64)]) -> String {
+        let mut patch = self.header.clone();
+        let mut delta = 0;
+        for (hunk, offset) in hunks {
+            // An insertion starts after its old line; a deletion leaves only the line before it.
+            let new_start = hunk.old_start as i64 + offset + delta + i64::from(hunk.old_count == 0)
+                - i64::from(hunk.new_count == 0);
+            patch.push_str(&hunk.render(*offset, new_start - hunk.new_start as i64));
+            delta += hunk.delta();
+        }
+        patch
+    }
+
+    /// Renders the whole file diff unchanged.
+    pub fn render_all(&self) -> String {
+        let hunks: Vec<(&Hunk, i64)> = self.hunks.iter().map(|hunk| (hunk, 0)).collect();
+        self.render(&hunks)
+    }
+}
+
+/// staged_patch returns the staged changes as a patch, with `context` lines around each hunk.
+pub fn staged_patch(context: u32) -> Result<String> {
+    raw(&[
+        "diff",
+        "--cached",
+        "--no-color",
+        "--no-ext-diff",
+        "--binary",
+        &format!("-U{}", context),
+    ])
+}
+
+/// staged returns the staged changes per file, without context lines.
+pub fn staged() -> Result<Vec<FileDiff>> {
+    Ok(parse(&staged_patch(0)?))
+}
+
+/// range returns the changes from `from` to `to` per file, without context lines.
+pub fn range(from: &str, to: &str) -> Result<Vec<FileDiff>> {
+    let patch = raw(&["diff", "--no-color", "--no-ext-diff", "--binary", "-U0", from, to])?;
+    Ok(parse(&patch))
+}
+
+/// between returns the zero-context hunks that turn `path` at `from` into `path` at `to`.
+pub fn between(from: &str, to: &str, path: &str) -> Result<Vec<Hunk>> {
+    let patch = raw(&["diff", "--no-color", "--no-ext-diff", "-U0", from, to, "--", path])?;
+    Ok(parse(&patch).into_iter().flat_map(|file| file.hunks).collect())
+}
+
+/// blame returns the commit that last touched each line from `start` to `end` of `path` at `rev`.
+pub fn blame(rev: &str, path: &str, start: u32, end: u32) -> Result<Vec<String>> {
+    let output = super::run(&["blame", "-l", "-s", "-L", &format!("{},{}", start, end), rev, "--", path])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|hash| hash.trim_start_matches('^').to_string())
+        .collect())
+}
+
+/// apply applies a patch read from memory, passing `args` to `git apply`.
+pub fn apply(patch: &str, args: &[&str]) -> Result<()> {
+    apply_with(Command::new("git"), patch, args)
+}
+
+/// apply_to_tree applies a zero-context patch on top of `base` without touching the index or
+/// working tree, and returns the resulting tree.
+pub fn apply_to_tree(base: &str, patch: &str) -> Result<String> {
+    let index = super::run(&["rev-parse", "--git-path", "zyra-apply.index"])?;
+    let with_index = || {
+        let mut cmd = Command::new("git");
+        cmd.env("GIT_INDEX_FILE", &index);
+        cmd
+    };
+
+    let result = (|| {
+        output(with_index().args(["read-tree", base]))?;
+        apply_with(with_index(), patch, &["--cached", "--unidiff-zero"])?;
+        Ok(output(with_index().arg("write-tree"))?.trim().to_string())
+    })();
+    let _ = fs::remove_file(&index);
+    result
+}
+
+/// parse splits a unified diff into files and hunks.
+pub fn parse(patch: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+
+    for line in patch.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            files.push(FileDiff {
+                path: String::new(),
+                header: String::new(),
+                hunks: Vec::new(),
+            });
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if let Some(hunk) = parse_hunk_header(line) {
+            file.hunks.push(hunk);
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.body.push_str(line);
+        } else {
+            if let Some(path) = line.strip_prefix("+++ b/").or_else(|| line.strip_prefix("--- a/")) {
+                file.path = path.trim_end_matches('\n').to_string();
+            }
+            file.header.push_str(line);
+        }
+    }
+
+    for file in files.iter_mut().filter(|file| file.path.is_empty()) {
+        let first = file.header.lines().next().unwrap_or_default();
+        if let Some((_, path)) = first.rsplit_once(" b/") {
+            file.path = path.to_string();
+        }
+    }
+    files
+}
+
+/// Parses `@@ -a,b +c,d @@`; a missing count means one line.
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let ranges = line.strip_prefix("@@ -")?.split(" @@").next()?;
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |text: &str| -> Option<(u32, u32)> {
+        match text.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((text.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = range(old)?;
+    let (new_start, new_count) = range(new)?;
+
+    Some(Hunk {
+        old_start,
+        old_count,
+        new_start,
+        new_count,
+        body: String::new(),
+    })
+}
+
+/// Runs git and returns its untrimmed stdout, which matters for patches.
+fn raw(args: &[&str]) -> Result<String> {
+    output(Command::new("git").args(args))
+}
+
+fn output(cmd: &mut Command) -> Result<String> {
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(anyhow!("{}", String::from_utf8(output.stderr)?.trim()));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn apply_with(mut cmd: Command, patch: &str, args: &[&str]) -> Result<()> {
+    let mut child = cmd
+        .arg("apply")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Could not write the patch to git apply"))?
+        .write_all(patch.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!("{}", String::from_utf8(output.stderr)?.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                     index 1111111..2222222 100644\n\
+                     --- a/src/lib.rs\n\
+                     +++ b/src/lib.rs\n\
+                     @@ -3 +3,2 @@ fn main() {\n\
+                     -    old();\n\
+                     +    new();\n\
+                     +    newer();\n\
+                     @@ -10,0 +12,2 @@\n\
+                     +    one();\n\
+                     +    two();\n\
+                     diff --git a/notes.txt b/notes.txt\n\
+                     new file mode 100644\n\
+                     index 0000000..3333333\n\
+                     --- /dev/null\n\
+                     +++ b/notes.txt\n\
+                     @@ -0,0 +1 @@\n\
+                     +hello\n";
+
+        let files = parse(patch);
+        assert_eq!(files.len(), 2);
+
+        let lib = &files[0];
+        assert_eq!(lib.path, "src/lib.rs");
+        assert!(lib.is_line_edit());
+        assert_eq!(lib.hunks.len(), 2);
+        assert_eq!((lib.hunks[0].old_start, lib.hunks[0].old_count), (3, 1));
+        assert_eq!((lib.hunks[1].old_start, lib.hunks[1].old_count), (10, 0));
+        assert_eq!(lib.hunks[1].old_end(), 10);
+        assert_eq!(lib.hunks[1].render(2, 1), "@@ -12,0 +13,2 @@\n+    one();\n+    two();\n");
+
+        // Without the first hunk, which adds a line, the insertion starts a line higher.
+        assert!(lib.render_all().contains("@@ -10,0 +12,2 @@\n"));
+        let alone = lib.render(&[(&lib.hunks[1], 0)]);
+        assert!(alone.ends_with("@@ -10,0 +11,2 @@\n+    one();\n+    two();\n"));
+
+        let notes = &files[1];
+        assert_eq!(notes.path, "notes.txt");
+        assert!(!notes.is_line_edit());
+        assert_eq!(notes.render_all().lines().count(), 7);
+    }
+}
@@ -1,3 +1,20 @@
+use anyhow::{anyhow, Result};
+use std::process::Command;
+
 pub mod branch;
 pub mod commit;
+pub mod diff;
+pub mod rebase;
+pub mod remote;
 pub mod repo;
+
+/// run executes git with the given arguments and returns its trimmed stdout.
+pub(crate) fn run(args: &[&str]) -> Result<String> {
+    let output = Command::new("git").args(args).output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!("{}", String::from_utf8(output.stderr)?.trim()));
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
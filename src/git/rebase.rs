@@ -0,0 +1,67 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Result of a rebase step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The rebase finished.
+    Done,
+    /// The rebase stopped on conflicts and is waiting for the user.
+    Conflict,
+}
+
+/// onto replays the commits of `branch` after `upstream` on top of `onto`.
+pub fn onto(onto: &str, upstream: &str, branch: &str, autostash: bool) -> Result<Outcome> {
+    let mut cmd = Command::new("git");
+    cmd.arg("rebase");
+    if autostash {
+        cmd.arg("--autostash");
+    }
+    cmd.arg("--onto").arg(onto).arg(upstream).arg(branch);
+
+    finish(cmd)
+}
+
+/// resume continues a rebase stopped on conflicts, without opening an editor.
+pub fn resume() -> Result<Outcome> {
+    let mut cmd = Command::new("git");
+    cmd.env("GIT_EDITOR", "true").arg("rebase").arg("--continue");
+
+    finish(cmd)
+}
+
+/// abort gives up on the rebase in progress.
+pub fn abort() -> Result<()> {
+    super::run(&["rebase", "--abort"])?;
+    Ok(())
+}
+
+/// in_progress checks whether git is in the middle of a rebase.
+pub fn in_progress() -> bool {
+    ["rebase-merge", "rebase-apply"].iter().any(|dir| {
+        super::run(&["rev-parse", "--git-path", dir])
+            .map(|path| Path::new(&path).exists())
+            .unwrap_or(false)
+    })
+}
+
+/// conflicted_files lists the paths with unresolved conflicts.
+pub fn conflicted_files() -> Vec<String> {
+    super::run(&["diff", "--name-only", "--diff-filter=U"])
+        .map(|output| output.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn finish(mut cmd: Command) -> Result<Outcome> {
+    let output = cmd.output()?;
+    if output.status.success() {
+        return Ok(Outcome::Done);
+    }
+
+    if in_progress() {
+        return Ok(Outcome::Conflict);
+    }
+
+    Err(anyhow!("{}", String::from_utf8(output.stderr)?.trim()))
+}
@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Result};
+use std::process::Command;
+
+/// Result of a push protected by a lease.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lease {
+    /// The remote branch was where we expected, and now holds the pushed commit.
+    Pushed,
+    /// The remote branch moved since we last saw it, so nothing was pushed.
+    Stale,
+}
+
+/// Pushes a local branch to the branch of the same name on a remote; `set_upstream` also makes
+/// that remote branch the local branch's upstream.
+pub fn push(remote: &str, branch: &str, set_upstream: bool) -> Result<()> {
+    let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
+    let mut args = vec!["push", "--quiet"];
+    if set_upstream {
+        args.push("--set-upstream");
+    }
+    args.extend([remote, &refspec]);
+
+    super::run(&args)?;
+    Ok(())
+}
+
+/// Fetches every branch of a remote, dropping remote-tracking branches that were deleted.
+pub fn fetch(remote: &str) -> Result<()> {
+    super::run(&["fetch", "--quiet", "--prune", remote])?;
+    Ok(())
+}
+
+/// Deletes a branch on a remote.
+pub fn delete(remote: &str, branch: &str) -> Result<()> {
+    super::run(&["push", "--quiet", remote, "--delete", branch])?;
+    Ok(())
+}
+
+/// Force-pushes a local branch to the same-named remote branch, but only if the remote branch is
+/// still at `expected`, or does not exist when `expected` is None.
+pub fn push_with_lease(remote: &str, branch: &str, expected: Option<&str>) -> Result<Lease> {
+    let lease = format!(
+        "--force-with-lease=refs/heads/{}:{}",
+        branch,
+        expected.unwrap_or_default()
+    );
+    let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
+    let output = Command::new("git")
+        .args(["push", "--quiet", "--porcelain", &lease, remote, &refspec])
+        .output()?;
+
+    if output.status.success() {
+        return Ok(Lease::Pushed);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.contains("stale info") || stdout.contains("fetch first") {
+        return Ok(Lease::Stale);
+    }
+    Err(anyhow!("{}", String::from_utf8_lossy(&output.stderr).trim()))
+}
+
+/// Gets the commit a remote-tracking branch points at, as of the last fetch.
+pub fn tracking_hash(remote: &str, branch: &str) -> Option<String> {
+    super::run(&["rev-parse", "--verify", "--quiet", &format!("refs/remotes/{}/{}", remote, branch)]).ok()
+}
+
+/// Checks whether a remote is configured.
+pub fn exists(remote: &str) -> bool {
+    super::run(&["remote", "get-url", remote]).is_ok()
+}
+
+/// Gets the URL of a remote.
+pub fn url(remote: &str) -> Result<String> {
+    super::run(&["remote", "get-url", remote])
+}
@@ -1,5 +1,5 @@
-use anyhow::{anyhow, Result};
-use std::{path::Path, process::Command};
+use anyhow::Result;
+use std::{path::PathBuf, process::Command};
 
 /// is_repo returns if user is in an active repo.
 pub fn is_repo() -> Result<bool> {
@@ -13,3 +13,18 @@ pub fn is_repo() -> Result<bool> {
         Ok(false)
     }
 }
+
+/// root returns the top-level directory of the working tree.
+pub fn root() -> Result<PathBuf> {
+    Ok(PathBuf::from(super::run(&["rev-parse", "--show-toplevel"])?))
+}
+
+/// config_value reads a git config value, if it is set.
+pub fn config_value(key: &str) -> Option<String> {
+    super::run(&["config", "--get", key]).ok().filter(|value| !value.is_empty())
+}
+
+/// hooks_dir returns the directory git runs hooks from, honouring `core.hooksPath`.
+pub fn hooks_dir() -> Result<PathBuf> {
+    Ok(PathBuf::from(super::run(&["rev-parse", "--git-path", "hooks"])?))
+}
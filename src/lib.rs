@@ -1,6 +1,8 @@
 pub mod app;
 pub mod cli;
+pub mod config;
 pub mod data;
 pub mod errors;
+pub mod forge;
 pub mod git;
 pub mod ui;
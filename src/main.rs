@@ -1,9 +1,14 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use zyra::cli::Run;
 use std::process::ExitCode;
 
 #[tokio::main]
 async fn main() -> ExitCode {
+    // Answers the shell's completion requests; see `zyra completions`.
+    clap_complete::CompleteEnv::with_factory(zyra::cli::Cmd::command)
+        .var(zyra::cli::completions::COMPLETE_VAR)
+        .complete();
+
     match zyra::cli::Cmd::parse().run().await {
         Ok(()) => ExitCode::SUCCESS,
         Err(err) => {
@@ -0,0 +1,282 @@
+//! Terminal rendering: colour detection, themes and glyph sets.
+//!
+//! Colour is enabled only when stdout is a terminal and `NO_COLOR` is unset (`CLICOLOR_FORCE`
+//! or `ZYRA_COLOR=always` force it on). Unicode glyphs are used on terminals and ASCII
+//! everywhere else, unless `ZYRA_GLYPHS` says otherwise. `ZYRA_THEME` picks the theme.
+//! Each variable falls back to the matching `ui.*` key in the config files.
+use colored::Color;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+/// What a piece of text represents; themes map each role to a style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The `[zyra]` prefix.
+    Brand,
+    /// Headings such as the stack name.
+    Title,
+    /// The marker of the branch HEAD is on.
+    Current,
+    /// Markers and text that should recede.
+    Muted,
+    /// Branch names on the current path.
+    Branch,
+    /// Branch names off the current path.
+    OtherBranch,
+    /// Commit hashes.
+    Hash,
+    /// Branch status.
+    Status,
+    /// Remote tracking information.
+    Remote,
+    /// Things that need the user's attention.
+    Warning,
+}
+
+/// Text attributes applied for a role.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dimmed: bool,
+}
+
+impl Style {
+    const fn new(color: Option<Color>, bold: bool, dimmed: bool) -> Self {
+        Style { color, bold, dimmed }
+    }
+}
+
+/// Colour themes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Default,
+    /// No colours, only bold and dim.
+    Mono,
+    /// Bright colours for dark or low-contrast terminals.
+    HighContrast,
+}
+
+impl Theme {
+    /// Names accepted by [`Theme::from_name`].
+    pub const NAMES: [&'static str; 3] = ["default", "mono", "high-contrast"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "default" => Some(Theme::Default),
+            "mono" => Some(Theme::Mono),
+            "high-contrast" => Some(Theme::HighContrast),
+            _ => None,
+        }
+    }
+
+    pub fn style(self, role: Role) -> Style {
+        match (self, role) {
+            (Theme::Mono, Role::Brand | Role::Title | Role::Current | Role::Branch | Role::Warning) => {
+                Style::new(None, true, false)
+            }
+            (Theme::Mono, Role::Muted) => Style::new(None, false, true),
+            (Theme::Mono, _) => Style::default(),
+
+            (Theme::Default, Role::Brand) => Style::new(Some(Color::BrightMagenta), false, false),
+            (Theme::Default, Role::Title) => Style::new(None, true, false),
+            (Theme::Default, Role::Current) => Style::new(Some(Color::BrightGreen), true, false),
+            (Theme::Default, Role::Muted) => Style::new(None, false, true),
+            (Theme::Default, Role::Branch) => Style::new(Some(Color::Yellow), true, false),
+            (Theme::Default, Role::OtherBranch) => Style::default(),
+            (Theme::Default, Role::Hash) => Style::new(Some(Color::Blue), false, false),
+            (Theme::Default, Role::Status) => Style::new(Some(Color::Green), false, false),
+            (Theme::Default, Role::Remote) => Style::new(Some(Color::Cyan), false, false),
+            (Theme::Default, Role::Warning) => Style::new(Some(Color::Red), true, false),
+
+            (Theme::HighContrast, Role::Brand) => Style::new(Some(Color::BrightMagenta), true, false),
+            (Theme::HighContrast, Role::Title) => Style::new(Some(Color::BrightWhite), true, false),
+            (Theme::HighContrast, Role::Current) => Style::new(Some(Color::BrightGreen), true, false),
+            (Theme::HighContrast, Role::Muted) => Style::new(Some(Color::White), false, false),
+            (Theme::HighContrast, Role::Branch) => Style::new(Some(Color::BrightYellow), true, false),
+            (Theme::HighContrast, Role::OtherBranch) => Style::new(Some(Color::BrightWhite), false, false),
+            (Theme::HighContrast, Role::Hash) => Style::new(Some(Color::BrightCyan), false, false),
+            (Theme::HighContrast, Role::Status) => Style::new(Some(Color::BrightGreen), false, false),
+            (Theme::HighContrast, Role::Remote) => Style::new(Some(Color::BrightBlue), false, false),
+            (Theme::HighContrast, Role::Warning) => Style::new(Some(Color::BrightRed), true, false),
+        }
+    }
+}
+
+/// Characters used to draw stacks.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Glyphs {
+    pub current: &'static str,
+    pub other: &'static str,
+    pub pipe: &'static str,
+    pub tee: &'static str,
+    pub elbow: &'static str,
+    pub arrow: &'static str,
+    pub ahead: &'static str,
+    pub behind: &'static str,
+}
+
+pub const UNICODE_GLYPHS: Glyphs = Glyphs {
+    current: "●",
+    other: "○",
+    pipe: "│",
+    tee: "├──",
+    elbow: "└──",
+    arrow: "➜",
+    ahead: "↑",
+    behind: "↓",
+};
+
+pub const ASCII_GLYPHS: Glyphs = Glyphs {
+    current: "*",
+    other: "o",
+    pipe: "|",
+    tee: "|--",
+    elbow: "`--",
+    arrow: "->",
+    ahead: "+",
+    behind: "-",
+};
+
+/// Resolved rendering settings.
+#[derive(Debug)]
+pub struct Ui {
+    pub color: bool,
+    pub theme: Theme,
+    pub glyphs: &'static Glyphs,
+}
+
+impl Ui {
+    pub fn new(color: bool, theme: Theme, ascii: bool) -> Self {
+        Ui {
+            color,
+            theme,
+            glyphs: if ascii { &ASCII_GLYPHS } else { &UNICODE_GLYPHS },
+        }
+    }
+
+    /// Settings for the current process, read from the environment, config and stdout.
+    pub fn detect() -> Self {
+        let config = crate::config::Config::load().unwrap_or_default().ui;
+        Self::from_env(
+            |key| {
+                std::env::var(key).ok().or_else(|| match key {
+                    "ZYRA_COLOR" => config.color.clone(),
+                    "ZYRA_GLYPHS" => config.glyphs.clone(),
+                    "ZYRA_THEME" => config.theme.clone(),
+                    _ => None,
+                })
+            },
+            std::io::stdout().is_terminal(),
+        )
+    }
+
+    /// Resolves settings from environment lookups and whether output goes to a terminal.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>, is_tty: bool) -> Self {
+        let set = |key: &str| var(key).is_some_and(|value| !value.is_empty());
+
+        let color = if set("NO_COLOR") {
+            false
+        } else if var("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+            true
+        } else {
+            match var("ZYRA_COLOR").as_deref() {
+                Some("always") => true,
+                Some("never") => false,
+                _ => is_tty,
+            }
+        };
+
+        let ascii = match var("ZYRA_GLYPHS").as_deref() {
+            Some("ascii") => true,
+            Some("unicode") => false,
+            _ => !is_tty,
+        };
+
+        let theme = var("ZYRA_THEME")
+            .and_then(|name| Theme::from_name(&name))
+            .unwrap_or_default();
+
+        Self::new(color, theme, ascii)
+    }
+
+    /// Styles text for a role, or returns it untouched when colour is off. The escape codes are
+    /// written here rather than by `colored`, whose on/off switch is process-wide.
+    pub fn paint(&self, role: Role, text: impl AsRef<str>) -> String {
+        let text = text.as_ref();
+        let style = self.theme.style(role);
+        let mut codes = Vec::new();
+        if style.bold {
+            codes.push("1".into());
+        }
+        if style.dimmed {
+            codes.push("2".into());
+        }
+        if let Some(color) = style.color {
+            codes.push(color.to_fg_str());
+        }
+        if !self.color || codes.is_empty() {
+            return text.to_string();
+        }
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
+static UI: OnceLock<Ui> = OnceLock::new();
+
+/// The settings used for all output. Also steers the `colored` crate so stray styling follows suit.
+pub fn current() -> &'static Ui {
+    UI.get_or_init(|| {
+        let ui = Ui::detect();
+        colored::control::set_override(ui.color);
+        ui
+    })
+}
+
+/// Shorthand for painting with the process-wide settings.
+pub fn paint(role: Role, text: impl AsRef<str>) -> String {
+    current().paint(role, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+    }
+
+    #[test]
+    fn test_tty_defaults_to_color_and_unicode() {
+        let ui = Ui::from_env(env(&[]), true);
+        assert!(ui.color);
+        assert_eq!(ui.glyphs, &UNICODE_GLYPHS);
+        assert_eq!(ui.theme, Theme::Default);
+    }
+
+    #[test]
+    fn test_pipe_defaults_to_plain_ascii() {
+        let ui = Ui::from_env(env(&[]), false);
+        assert!(!ui.color);
+        assert_eq!(ui.glyphs, &ASCII_GLYPHS);
+    }
+
+    #[test]
+    fn test_no_color_wins() {
+        let ui = Ui::from_env(env(&[("NO_COLOR", "1"), ("ZYRA_COLOR", "always")]), true);
+        assert!(!ui.color);
+        assert_eq!(ui.paint(Role::Warning, "text"), "text");
+    }
+
+    #[test]
+    fn test_overrides() {
+        let ui = Ui::from_env(
+            env(&[("ZYRA_COLOR", "always"), ("ZYRA_GLYPHS", "unicode"), ("ZYRA_THEME", "mono")]),
+            false,
+        );
+        assert!(ui.color);
+        assert_eq!(ui.glyphs, &UNICODE_GLYPHS);
+        assert_eq!(ui.theme, Theme::Mono);
+    }
+}
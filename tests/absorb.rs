@@ -0,0 +1,36 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn absorb_restages_insertions_below_absorbed_hunks() {
+    let repo = Repo::new();
+    let lines: Vec<String> = (1..=10).map(|n| format!("line {}", n)).collect();
+    repo.work_commit("f.txt", &(lines.join("\n") + "\n"));
+    repo.zyra_ok(&["init", "base", "--base", "main"]);
+
+    // The stack owns line 2; everything else comes from main.
+    let mut base = lines.clone();
+    base[1] = "line 2, edited".to_string();
+    repo.work_commit("f.txt", &(base.join("\n") + "\n"));
+    repo.zyra_ok(&["branch", "top"]);
+    repo.work_commit("g.txt", "g\n");
+
+    // Line 2 grows by two lines and goes to `base`; the insertion after line 8 stays staged.
+    let mut staged = base.clone();
+    staged.splice(1..2, ["line 2, edited again", "extra 1", "extra 2"].map(String::from));
+    staged.insert(10, "inserted".to_string());
+    std::fs::write(repo.work.join("f.txt"), staged.join("\n") + "\n").unwrap();
+    repo.work_git(&["add", "f.txt"]);
+
+    repo.zyra_ok(&["absorb", "--yes"]);
+
+    let mut absorbed = base.clone();
+    absorbed.splice(1..2, ["line 2, edited again", "extra 1", "extra 2"].map(String::from));
+    assert_eq!(repo.work_git(&["show", "base:f.txt"]), absorbed.join("\n"));
+    assert_eq!(repo.work_git(&["show", "top:f.txt"]), absorbed.join("\n"));
+    assert_eq!(std::fs::read_to_string(repo.work.join("f.txt")).unwrap(), staged.join("\n") + "\n");
+    let leftover = repo.work_git(&["diff", "--cached", "-U0", "--", "f.txt"]);
+    let hunks: Vec<&str> = leftover.lines().filter(|line| line.starts_with(['@', '+'])).collect();
+    assert_eq!(hunks, ["+++ b/f.txt", "@@ -10,0 +11 @@ line 8", "+inserted"]);
+}
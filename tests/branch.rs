@@ -0,0 +1,46 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn insert_with_an_existing_git_branch_name_changes_nothing() {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("s.txt", "s\n");
+    repo.zyra_ok(&["branch", "top"]);
+    repo.work_commit("top.txt", "top\n");
+    repo.work_git(&["branch", "taken", "main"]);
+    repo.zyra_ok(&["goto", "s"]);
+    let before = repo.metadata();
+
+    let output = repo.zyra(&["branch", "taken", "--insert"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+    assert_eq!(repo.metadata(), before);
+    assert_eq!(repo.branch("top").unwrap()["parent"], "s");
+
+    repo.zyra_ok(&["branch", "middle", "--insert"]);
+    assert_eq!(repo.branch("middle").unwrap()["parent"], "s");
+    assert_eq!(repo.branch("top").unwrap()["parent"], "middle");
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "middle");
+}
+
+#[test]
+fn next_and_prev_follow_an_inserted_branch() {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.zyra_ok(&["branch", "top"]);
+    repo.zyra_ok(&["goto", "s"]);
+    repo.zyra_ok(&["branch", "middle", "--insert"]);
+    let current = || repo.work_git(&["branch", "--show-current"]);
+
+    repo.zyra_ok(&["next"]);
+    assert_eq!(current(), "top");
+    assert!(!repo.zyra(&["next"]).status.success());
+    repo.zyra_ok(&["prev"]);
+    assert_eq!(current(), "middle");
+    repo.zyra_ok(&["prev"]);
+    assert_eq!(current(), "s");
+    repo.zyra_ok(&["next"]);
+    assert_eq!(current(), "middle");
+}
@@ -0,0 +1,257 @@
+//! Scratch repositories for driving the `zyra` binary in integration tests.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+/// Runs a command in `dir` with a fixed identity and no user configuration.
+pub fn run(dir: &Path, program: &str, args: &[&str], home: &Path, envs: &[(&str, &str)]) -> Output {
+    Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_AUTHOR_NAME", "zyra")
+        .env("GIT_AUTHOR_EMAIL", "zyra@example.com")
+        .env("GIT_COMMITTER_NAME", "zyra")
+        .env("GIT_COMMITTER_EMAIL", "zyra@example.com")
+        .envs(envs.iter().copied())
+        .output()
+        .unwrap()
+}
+
+pub struct Repo {
+    _tmp: TempDir,
+    pub home: PathBuf,
+    pub remote: PathBuf,
+    pub work: PathBuf,
+}
+
+impl Repo {
+    /// A working repository on `main` whose `origin` is a local bare repository.
+    pub fn new() -> Self {
+        let tmp = TempDir::new().unwrap();
+        let home = tmp.path().join("home");
+        let remote = tmp.path().join("remote.git");
+        let work = tmp.path().join("work");
+        std::fs::create_dir_all(&home).unwrap();
+        std::fs::create_dir_all(&work).unwrap();
+
+        let repo = Repo { _tmp: tmp, home, remote, work };
+        repo.git(repo.home.as_path(), &["init", "--quiet", "--bare", repo.remote.to_str().unwrap()]);
+        repo.git(&repo.work, &["init", "--quiet", "-b", "main"]);
+        repo.git(&repo.work, &["commit", "--quiet", "--allow-empty", "-m", "init"]);
+        repo.git(&repo.work, &["remote", "add", "origin", repo.remote.to_str().unwrap()]);
+        repo.git(&repo.work, &["push", "--quiet", "origin", "main"]);
+        repo
+    }
+
+    pub fn git(&self, dir: &Path, args: &[&str]) -> String {
+        let output = run(dir, "git", args, &self.home, &[]);
+        assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    /// Runs git in the working repository.
+    pub fn work_git(&self, args: &[&str]) -> String {
+        self.git(&self.work, args)
+    }
+
+    pub fn zyra(&self, args: &[&str]) -> Output {
+        self.zyra_with(args, &[])
+    }
+
+    pub fn zyra_with(&self, args: &[&str], envs: &[(&str, &str)]) -> Output {
+        run(&self.work, env!("CARGO_BIN_EXE_zyra"), args, &self.home, envs)
+    }
+
+    /// Runs zyra, expecting it to succeed, and returns its output.
+    pub fn zyra_ok(&self, args: &[&str]) -> String {
+        let output = self.zyra(args);
+        assert!(
+            output.status.success(),
+            "zyra {:?}: {}{}",
+            args,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    pub fn commit(&self, dir: &Path, file: &str, content: &str) {
+        std::fs::write(dir.join(file), content).unwrap();
+        self.git(dir, &["add", file]);
+        self.git(dir, &["commit", "--quiet", "-m", file]);
+    }
+
+    /// Commits a file in the working repository.
+    pub fn work_commit(&self, file: &str, content: &str) {
+        self.commit(&self.work, file, content)
+    }
+
+    pub fn rev(&self, rev: &str) -> String {
+        self.work_git(&["rev-parse", rev])
+    }
+
+    pub fn remote_tip(&self, branch: &str) -> String {
+        self.git(&self.remote, &["rev-parse", branch])
+    }
+
+    /// A clone of the remote, checked out on `branch`, standing in for another contributor.
+    pub fn clone_on(&self, branch: &str) -> PathBuf {
+        let other = self.work.with_file_name("other");
+        if !other.exists() {
+            self.git(self.home.as_path(), &["clone", "--quiet", self.remote.to_str().unwrap(), other.to_str().unwrap()]);
+        }
+        self.git(&other, &["fetch", "--quiet", "origin"]);
+        self.git(&other, &["switch", "--quiet", branch]);
+        self.git(&other, &["reset", "--quiet", "--hard", &format!("origin/{}", branch)]);
+        other
+    }
+
+    pub fn metadata(&self) -> serde_json::Value {
+        let path = self.work.join(".git").join("zyra-metadata.json");
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    pub fn set_metadata(&self, metadata: &serde_json::Value) {
+        let path = self.work.join(".git").join("zyra-metadata.json");
+        std::fs::write(path, serde_json::to_string_pretty(metadata).unwrap()).unwrap();
+    }
+
+    /// The recorded entry of a tracked branch.
+    pub fn branch(&self, name: &str) -> Option<serde_json::Value> {
+        self.metadata()["stacks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|stack| stack["branches"].as_array().unwrap().clone())
+            .find(|branch| branch["name"] == name)
+    }
+}
+
+/// A request received by [`MockForge`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// The `Authorization` header, if any.
+    pub authorization: Option<String>,
+    pub body: serde_json::Value,
+}
+
+/// A stand-in for the GitHub API that accepts every request and records it. Opened pull
+/// requests get number 100; every other answer echoes the number in the path.
+pub struct MockForge {
+    pub url: String,
+    requests: std::sync::Arc<std::sync::Mutex<Vec<Request>>>,
+}
+
+impl MockForge {
+    pub fn start() -> Self {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+
+                let mut length = 0;
+                let mut authorization = None;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("authorization")
+                    {
+                        authorization = Some(value.trim().to_string());
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+
+                let number: u32 = if method == "POST" && path.ends_with("/pulls") {
+                    100
+                } else {
+                    path.split('/').find_map(|part| part.parse().ok()).unwrap_or(1)
+                };
+                let answer = serde_json::json!({
+                    "number": number,
+                    "title": format!("PR {}", number),
+                    "body": "Description",
+                    "draft": false,
+                    "state": "open",
+                })
+                .to_string();
+                recorded.lock().unwrap().push(Request { method, path, authorization, body });
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    answer.len(),
+                    answer
+                )
+                .unwrap();
+            }
+        });
+        MockForge { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Points zyra in `repo` at this forge.
+    pub fn configure(&self, repo: &Repo) {
+        let dir = repo.home.join("zyra");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("config.toml"),
+            format!("[forge]\nprovider = \"github\"\nhost = \"{}\"\ntoken_env = \"ZYRA_TEST_TOKEN\"\n", self.url),
+        )
+        .unwrap();
+    }
+
+    /// Environment for zyra runs that talk to this forge.
+    pub fn env() -> [(&'static str, &'static str); 2] {
+        [("ZYRA_TEST_TOKEN", "secret"), ("NO_PROXY", "127.0.0.1")]
+    }
+}
+
+impl Repo {
+    /// Records a pull request number on a tracked branch.
+    pub fn set_pr(&self, branch: &str, number: u32) {
+        let mut metadata = self.metadata();
+        for stack in metadata["stacks"].as_array_mut().unwrap() {
+            if stack["head_branch"]["name"] == branch {
+                stack["head_branch"]["pr_id"] = number.into();
+            }
+            for entry in stack["branches"].as_array_mut().unwrap() {
+                if entry["name"] == branch {
+                    entry["pr_id"] = number.into();
+                }
+            }
+        }
+        self.set_metadata(&metadata);
+    }
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn doctor_reports_and_clears_a_stale_detached_head_context() {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("s.txt", "s\n");
+    repo.zyra_ok(&["goto", &repo.rev("s")]);
+    repo.work_git(&["switch", "--quiet", "main"]);
+
+    let output = repo.zyra(&["doctor"]);
+    assert!(!output.status.success());
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(report.contains("the detached-HEAD context for 's' is stale"), "{}", report);
+    assert_eq!(repo.metadata()["detached_head_context"]["branch_name"], "s");
+
+    repo.zyra_ok(&["doctor", "--fix"]);
+    assert!(repo.metadata()["detached_head_context"].is_null());
+    assert!(repo.zyra_ok(&["doctor"]).contains("No problems found."));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{MockForge, Repo};
+
+/// A stack `s` -> `a` -> `b` -> `c`, with one file per branch, checked out on `b`.
+fn stack() -> Repo {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("s.txt", "s\n");
+    for name in ["a", "b", "c"] {
+        repo.zyra_ok(&["branch", name]);
+        repo.work_commit(&format!("{}.txt", name), &format!("{}\n", name));
+    }
+    repo.zyra_ok(&["goto", "b"]);
+    repo
+}
+
+#[test]
+fn fold_merges_the_branch_into_its_parent() {
+    let repo = stack();
+    let forge = MockForge::start();
+    forge.configure(&repo);
+    repo.set_pr("b", 2);
+    repo.set_pr("c", 3);
+    let b_tip = repo.rev("b");
+
+    let output = repo.zyra_with(&["fold"], &MockForge::env());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(repo.rev("a"), b_tip);
+    assert!(repo.work_git(&["branch", "--list", "b"]).is_empty());
+    assert!(repo.branch("b").is_none());
+    assert_eq!(repo.branch("c").unwrap()["parent"], "a");
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "a");
+
+    let requests = forge.requests();
+    assert!(requests.iter().any(|r| r.method == "PATCH" && r.path.ends_with("/pulls/2") && r.body["state"] == "closed"));
+    assert!(requests.iter().any(|r| r.method == "PATCH" && r.path.ends_with("/pulls/3") && r.body["base"] == "a"));
+}
+
+#[test]
+fn fold_keep_replaces_the_parent() {
+    let repo = stack();
+    let b_tip = repo.rev("b");
+
+    repo.zyra_ok(&["fold", "--keep"]);
+
+    assert_eq!(repo.rev("b"), b_tip);
+    assert!(repo.work_git(&["branch", "--list", "a"]).is_empty());
+    assert!(repo.branch("a").is_none());
+    assert_eq!(repo.branch("b").unwrap()["parent"], "s");
+    assert_eq!(repo.branch("c").unwrap()["parent"], "b");
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "b");
+}
+
+/// The stack with a sibling `d` of `b` that conflicts with it once `a` takes b's commits, and
+/// pull requests for `b` and `c` on a mock forge.
+fn conflicting() -> (Repo, MockForge) {
+    let repo = stack();
+    repo.zyra_ok(&["goto", "a"]);
+    repo.zyra_ok(&["branch", "d"]);
+    repo.work_commit("b.txt", "d\n");
+    repo.zyra_ok(&["goto", "b"]);
+    let forge = MockForge::start();
+    forge.configure(&repo);
+    repo.set_pr("b", 2);
+    repo.set_pr("c", 3);
+    (repo, forge)
+}
+
+#[test]
+fn abort_after_a_conflicting_fold_restores_the_removed_branch() {
+    let (repo, forge) = conflicting();
+    let (a_tip, b_tip) = (repo.rev("a"), repo.rev("b"));
+
+    let output = repo.zyra_with(&["fold"], &MockForge::env());
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("stopped on conflicts"));
+
+    repo.zyra_ok(&["abort"]);
+    assert_eq!(repo.rev("a"), a_tip);
+    assert_eq!(repo.rev("b"), b_tip);
+    assert_eq!(repo.branch("b").unwrap()["parent"], "a");
+    assert_eq!(repo.branch("c").unwrap()["parent"], "b");
+    // The pull requests were never touched.
+    assert!(forge.requests().is_empty());
+}
+
+#[test]
+fn continue_after_a_conflicting_fold_updates_the_pull_requests() {
+    let (repo, forge) = conflicting();
+
+    assert!(!repo.zyra_with(&["fold"], &MockForge::env()).status.success());
+    assert!(forge.requests().is_empty());
+
+    std::fs::write(repo.work.join("b.txt"), "b and d\n").unwrap();
+    repo.work_git(&["add", "b.txt"]);
+    let output = repo.zyra_with(&["continue"], &MockForge::env());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let requests = forge.requests();
+    assert!(requests.iter().any(|r| r.method == "PATCH" && r.path.ends_with("/pulls/2") && r.body["state"] == "closed"));
+    assert!(requests.iter().any(|r| r.method == "PATCH" && r.path.ends_with("/pulls/3") && r.body["base"] == "a"));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::MockForge;
+use zyra::forge::Forge;
+
+fn connect(mock: &MockForge) -> Forge {
+    Forge::new(&mock.url, "acme/widgets", "secret")
+}
+
+#[tokio::test]
+async fn pull_requests_are_read_and_opened() {
+    let mock = MockForge::start();
+    let forge = connect(&mock);
+
+    let pr = forge.pull_request(7).await.unwrap();
+    assert_eq!((pr.number, pr.state.as_str(), pr.body.as_deref()), (7, "open", Some("Description")));
+    let number = forge.open("feature", "main", "Feature", Some("Body"), true).await.unwrap();
+    assert_eq!(number, 100);
+
+    let requests = mock.requests();
+    assert_eq!((requests[0].method.as_str(), requests[0].path.as_str()), ("GET", "/repos/acme/widgets/pulls/7"));
+    assert_eq!(requests[0].authorization.as_deref(), Some("Bearer secret"));
+    assert_eq!((requests[1].method.as_str(), requests[1].path.as_str()), ("POST", "/repos/acme/widgets/pulls"));
+    assert_eq!(
+        requests[1].body,
+        serde_json::json!({ "title": "Feature", "body": "Body", "head": "feature", "base": "main", "draft": true })
+    );
+}
+
+#[tokio::test]
+async fn pull_requests_are_retargeted_and_closed() {
+    let mock = MockForge::start();
+    let forge = connect(&mock);
+
+    forge.set_base(3, "a").await.unwrap();
+    forge.close(2, "Folded into `a`.").await.unwrap();
+
+    let requests: Vec<(String, String, serde_json::Value)> = mock
+        .requests()
+        .into_iter()
+        .map(|request| (request.method, request.path, request.body))
+        .collect();
+    assert_eq!(
+        requests,
+        [
+            ("PATCH".into(), "/repos/acme/widgets/pulls/3".into(), serde_json::json!({ "base": "a" })),
+            ("POST".into(), "/repos/acme/widgets/issues/2/comments".into(), serde_json::json!({ "body": "Folded into `a`." })),
+            ("PATCH".into(), "/repos/acme/widgets/pulls/2".into(), serde_json::json!({ "state": "closed" })),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn errors_from_the_forge_are_reported() {
+    // Nothing listens on a port that was just released.
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let forge = Forge::new(&format!("http://127.0.0.1:{}", port), "acme/widgets", "secret");
+    assert!(forge.set_base(3, "a").await.is_err());
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::Repo;
+
+/// main <- s (two commits) <- a <- b, with `b` checked out.
+fn stack() -> Repo {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("s1.txt", "s1\n");
+    repo.work_commit("s2.txt", "s2\n");
+    repo.zyra_ok(&["branch", "a"]);
+    repo.work_commit("a.txt", "a\n");
+    repo.zyra_ok(&["branch", "b"]);
+    repo.work_commit("b.txt", "b\n");
+    repo
+}
+
+fn context(repo: &Repo) -> serde_json::Value {
+    repo.metadata()["detached_head_context"].clone()
+}
+
+#[test]
+fn goto_commit_detaches_within_its_owner() {
+    let repo = stack();
+    let commit = repo.rev("s~1");
+
+    repo.zyra_ok(&["goto", &commit]);
+    assert_eq!(repo.rev("HEAD"), commit);
+    assert_eq!(context(&repo)["branch_name"], "s");
+
+    let log = repo.zyra_ok(&["log", "--verbose"]);
+    let marked = log.lines().find(|line| line.contains("(HEAD)")).unwrap();
+    assert!(marked.contains(&commit[..7]));
+    assert!(log.contains(&format!("(detached at {})", &commit[..7])));
+
+    // Read-only commands see the owner; rewriting ones refuse.
+    let json: serde_json::Value = serde_json::from_str(&repo.zyra_ok(&["log", "--json"])).unwrap();
+    assert_eq!(json["stack"]["current_branch"], "s");
+    let output = repo.zyra(&["create", "x", "-m", "x"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("HEAD is detached at a commit of 's'"));
+}
+
+#[test]
+fn a_stale_context_is_ignored_without_writing_it() {
+    let repo = stack();
+    repo.zyra_ok(&["goto", &repo.rev("a")]);
+    assert_eq!(context(&repo)["branch_name"], "a");
+
+    // Leaving with plain git makes the context stale; showing the stack must not rewrite it.
+    repo.work_git(&["switch", "b"]);
+    let path = repo.work.join(".git/zyra-metadata.json");
+    let stored = std::fs::read_to_string(&path).unwrap();
+    let log = repo.zyra_ok(&["log", "--verbose"]);
+    assert!(!log.contains("detached at"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), stored);
+
+    // Commands that save drop it.
+    repo.zyra_ok(&["goto", "a"]);
+    assert!(context(&repo).is_null());
+}
+
+#[test]
+fn names_that_resolve_outside_the_stack_fall_back_to_branch_names() {
+    let repo = stack();
+    repo.zyra_ok(&["branch", "api"]);
+    repo.work_git(&["tag", "ap", "main"]);
+    repo.work_git(&["branch", "ai", "main"]);
+    repo.zyra_ok(&["goto", "s"]);
+
+    // A tag and an untracked branch on the base both name commits, but no branch of the stack
+    // owns them, so the names are matched against the tracked branches instead.
+    repo.zyra_ok(&["goto", "ap"]);
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "api");
+    repo.zyra_ok(&["goto", "s"]);
+    repo.zyra_ok(&["goto", "ai"]);
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "api");
+    assert!(context(&repo).is_null());
+}
+
+#[test]
+fn a_word_that_names_nothing_is_not_treated_as_a_commit() {
+    let repo = stack();
+    for word in ["zzz", "deadbeef"] {
+        let output = repo.zyra(&["goto", word]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&format!("Could not find stack, branch, or commit '{}'", word)), "{}", stderr);
+        assert_eq!(repo.work_git(&["branch", "--show-current"]), "b");
+        assert!(context(&repo).is_null());
+    }
+}
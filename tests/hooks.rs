@@ -0,0 +1,71 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{run, Repo};
+use std::os::unix::fs::PermissionsExt;
+
+/// main <- s <- a, with the hooks installed and `s` checked out.
+fn stack() -> Repo {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("s.txt", "s\n");
+    repo.zyra_ok(&["branch", "a"]);
+    repo.work_commit("a.txt", "a\n");
+    repo.zyra_ok(&["goto", "s"]);
+    repo.zyra_ok(&["hooks", "install"]);
+    repo
+}
+
+#[test]
+fn a_commit_on_a_parent_is_recorded_and_its_child_reported() {
+    let repo = stack();
+    let old_tip = repo.rev("s");
+    std::fs::write(repo.work.join("s.txt"), "s2\n").unwrap();
+    let output = run(&repo.work, "git", &["commit", "--quiet", "-am", "s2"], &repo.home, &[]);
+    assert!(output.status.success());
+
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'a' need(s) a restack"));
+    assert_eq!(repo.branch("s").unwrap()["commit_hash"], repo.rev("s"));
+    assert_eq!(repo.branch("a").unwrap()["parent_hash"], old_tip);
+}
+
+#[test]
+fn an_existing_hook_still_runs() {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    let hooks = repo.work.join(".git/hooks");
+    std::fs::write(hooks.join("post-commit"), "#!/bin/sh\ntouch \"$(git rev-parse --git-dir)/user-hook-ran\"\n").unwrap();
+    std::fs::set_permissions(hooks.join("post-commit"), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    repo.zyra_ok(&["hooks", "install"]);
+    assert!(hooks.join("post-commit.user").exists());
+    repo.work_commit("s.txt", "s\n");
+
+    assert!(repo.work.join(".git/user-hook-ran").exists());
+    assert_eq!(repo.branch("s").unwrap()["commit_hash"], repo.rev("s"));
+}
+
+#[test]
+fn install_writes_nothing_when_a_hook_conflicts() {
+    let repo = Repo::new();
+    let hooks = repo.work.join(".git/hooks");
+    std::fs::write(hooks.join("post-commit"), "#!/bin/sh\n").unwrap();
+    std::fs::write(hooks.join("post-checkout"), "#!/bin/sh\n").unwrap();
+    std::fs::write(hooks.join("post-checkout.user"), "#!/bin/sh\n").unwrap();
+
+    assert!(!repo.zyra(&["hooks", "install"]).status.success());
+    assert_eq!(std::fs::read_to_string(hooks.join("post-commit")).unwrap(), "#!/bin/sh\n");
+    assert!(!hooks.join("post-commit.user").exists());
+    assert!(!hooks.join("post-rewrite").exists());
+}
+
+#[test]
+fn switching_away_drops_a_stale_detached_head_context() {
+    let repo = stack();
+    repo.zyra_ok(&["goto", &repo.rev("s")]);
+    assert_eq!(repo.metadata()["detached_head_context"]["branch_name"], "s");
+
+    repo.work_git(&["switch", "--quiet", "a"]);
+    assert!(repo.metadata()["detached_head_context"].is_null());
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn init_with_an_existing_git_branch_name_records_nothing() {
+    let repo = Repo::new();
+    repo.work_git(&["branch", "taken", "main"]);
+
+    let output = repo.zyra(&["init", "taken", "--base", "main"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+    assert!(!repo.work.join(".git/zyra-metadata.json").exists());
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "main");
+}
+
+#[test]
+fn init_that_cannot_create_its_branch_records_nothing() {
+    let repo = Repo::new();
+    // git refuses the name, since `s` would have to be a directory for `s/x` to exist.
+    repo.work_git(&["branch", "s/x", "main"]);
+
+    assert!(!repo.zyra(&["init", "s", "--base", "main"]).status.success());
+    assert!(!repo.work.join(".git/zyra-metadata.json").exists());
+
+    repo.zyra_ok(&["init", "t", "--base", "main"]);
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "t");
+    assert_eq!(repo.metadata()["stacks"][0]["name"], "t");
+}
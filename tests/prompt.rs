@@ -0,0 +1,59 @@
+mod common;
+
+use common::Repo;
+
+/// main <- s <- a, with `a` checked out.
+fn stack() -> Repo {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("s.txt", "s\n");
+    repo.zyra_ok(&["branch", "a"]);
+    repo.work_commit("a.txt", "a\n");
+    repo
+}
+
+fn prompt(repo: &Repo) -> String {
+    repo.zyra_ok(&["prompt"]).trim().to_string()
+}
+
+#[test]
+fn the_cached_segment_is_used_until_head_moves() {
+    let repo = stack();
+    assert_eq!(prompt(&repo), "s:a (2/2)");
+
+    // While HEAD and the metadata stay put, the cache answers.
+    let path = repo.work.join(".git/zyra-prompt.json");
+    let mut cache: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    cache["segment"]["branch"] = "cached".into();
+    std::fs::write(&path, cache.to_string()).unwrap();
+    assert_eq!(prompt(&repo), "s:cached (2/2)");
+
+    // A new commit changes the key, so the segment is worked out again.
+    repo.work_commit("a2.txt", "a2\n");
+    assert_eq!(prompt(&repo), "s:a (2/2)");
+}
+
+#[test]
+fn a_detached_head_shows_its_branch_only_while_on_one_of_its_commits() {
+    let repo = stack();
+    repo.zyra_ok(&["goto", &repo.rev("s")]);
+    assert_eq!(prompt(&repo), "s:s (1/2, detached)");
+
+    // Detaching elsewhere with plain git leaves the context behind, which the prompt ignores.
+    repo.work_git(&["switch", "--quiet", "-c", "other", "main"]);
+    repo.work_commit("other.txt", "other\n");
+    repo.work_git(&["switch", "--quiet", "--detach", "other"]);
+    assert_eq!(repo.metadata()["detached_head_context"]["branch_name"], "s");
+    assert_eq!(prompt(&repo), "");
+}
+
+#[test]
+fn a_commit_on_the_parent_invalidates_the_cached_segment() {
+    let repo = stack();
+    assert_eq!(prompt(&repo), "s:a (2/2)");
+
+    repo.work_git(&["switch", "--quiet", "s"]);
+    repo.work_commit("s2.txt", "s2\n");
+    repo.work_git(&["switch", "--quiet", "a"]);
+    assert_eq!(prompt(&repo), "s:a (2/2, needs restack)");
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn push_stack_sets_upstreams_and_forces_rewrites() {
+    let repo = Repo::new();
+    assert!(repo.zyra(&["init", "base"]).status.success());
+    repo.commit(&repo.work, "a.txt", "a");
+    assert!(repo.zyra(&["branch", "top"]).status.success());
+    repo.commit(&repo.work, "b.txt", "b");
+
+    let output = repo.zyra(&["push"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.remote_tip("base"), repo.git(&repo.work, &["rev-parse", "base"]));
+    assert_eq!(repo.remote_tip("top"), repo.git(&repo.work, &["rev-parse", "top"]));
+    assert_eq!(repo.git(&repo.work, &["rev-parse", "--abbrev-ref", "top@{upstream}"]), "origin/top");
+
+    // Rewritten history is force-pushed, since the remote is still where we left it.
+    repo.git(&repo.work, &["commit", "--quiet", "--amend", "-m", "b, reworded"]);
+    let output = repo.zyra(&["push"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.remote_tip("top"), repo.git(&repo.work, &["rev-parse", "top"]));
+}
+
+#[test]
+fn push_refuses_to_overwrite_remote_changes() {
+    let repo = Repo::new();
+    assert!(repo.zyra(&["init", "base"]).status.success());
+    repo.commit(&repo.work, "a.txt", "a");
+    assert!(repo.zyra(&["push"]).status.success());
+
+    // Someone else pushes to the same branch.
+    let other = repo.work.with_file_name("other");
+    repo.git(repo.home.as_path(), &["clone", "--quiet", repo.remote.to_str().unwrap(), other.to_str().unwrap()]);
+    repo.git(&other, &["switch", "--quiet", "base"]);
+    repo.commit(&other, "theirs.txt", "theirs");
+    repo.git(&other, &["push", "--quiet", "origin", "base"]);
+    let theirs = repo.remote_tip("base");
+
+    repo.git(&repo.work, &["commit", "--quiet", "--amend", "-m", "a, reworded"]);
+    let output = repo.zyra(&["push"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has changed since zyra last pushed it"));
+    assert_eq!(repo.remote_tip("base"), theirs);
+}
+
+#[test]
+fn push_suggests_fetching_a_branch_it_has_never_seen() {
+    let repo = Repo::new();
+    assert!(repo.zyra(&["init", "base"]).status.success());
+    repo.commit(&repo.work, "a.txt", "a");
+
+    // Someone else pushed a branch of the same name before we ever fetched it.
+    let other = repo.clone_on("main");
+    repo.git(&other, &["switch", "--quiet", "-c", "base"]);
+    repo.commit(&other, "theirs.txt", "theirs");
+    repo.git(&other, &["push", "--quiet", "origin", "base"]);
+    let theirs = repo.remote_tip("base");
+
+    let output = repo.zyra(&["push"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Run `git fetch origin`"), "{}", stderr);
+    assert!(!stderr.contains("since zyra last pushed it"));
+    assert_eq!(repo.remote_tip("base"), theirs);
+}
+
+#[test]
+fn pull_fast_forwards_and_restacks_children() {
+    let repo = Repo::new();
+    assert!(repo.zyra(&["init", "base"]).status.success());
+    repo.commit(&repo.work, "a.txt", "a");
+    assert!(repo.zyra(&["branch", "top"]).status.success());
+    repo.commit(&repo.work, "b.txt", "b");
+    assert!(repo.zyra(&["push"]).status.success());
+
+    // A reviewer pushes a fix to the bottom branch.
+    let other = repo.work.with_file_name("other");
+    repo.git(repo.home.as_path(), &["clone", "--quiet", repo.remote.to_str().unwrap(), other.to_str().unwrap()]);
+    repo.git(&other, &["switch", "--quiet", "base"]);
+    repo.commit(&other, "fix.txt", "fix");
+    repo.git(&other, &["push", "--quiet", "origin", "base"]);
+
+    let output = repo.zyra(&["pull"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.git(&repo.work, &["rev-parse", "base"]), repo.remote_tip("base"));
+    repo.git(&repo.work, &["merge-base", "--is-ancestor", "base", "top"]);
+    assert_eq!(repo.git(&repo.work, &["branch", "--show-current"]), "top");
+}
+
+/// base <- top, pushed, then a reviewer commits `file` with `content` on `base`.
+fn reviewed(file: &str, content: &str) -> Repo {
+    let repo = Repo::new();
+    assert!(repo.zyra(&["init", "base"]).status.success());
+    repo.commit(&repo.work, "a.txt", "a\n");
+    assert!(repo.zyra(&["branch", "top"]).status.success());
+    repo.commit(&repo.work, "b.txt", "b\n");
+    assert!(repo.zyra(&["push"]).status.success());
+
+    let other = repo.clone_on("base");
+    repo.commit(&other, file, content);
+    repo.git(&other, &["push", "--quiet", "origin", "base"]);
+    repo
+}
+
+/// Adds a local commit to `base` and returns to `top`.
+fn commit_on_base(repo: &Repo, file: &str, content: &str) {
+    repo.git(&repo.work, &["switch", "--quiet", "base"]);
+    repo.commit(&repo.work, file, content);
+    repo.git(&repo.work, &["switch", "--quiet", "top"]);
+}
+
+#[test]
+fn pull_rebases_diverged_branches_onto_the_remote() {
+    let repo = reviewed("fix.txt", "fix\n");
+    commit_on_base(&repo, "local.txt", "local\n");
+
+    let output = repo.zyra(&["pull"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    repo.git(&repo.work, &["merge-base", "--is-ancestor", "origin/base", "base"]);
+    assert_eq!(repo.git(&repo.work, &["show", "base:local.txt"]), "local");
+    repo.git(&repo.work, &["merge-base", "--is-ancestor", "base", "top"]);
+    assert_eq!(repo.branch("base").unwrap()["remote_hash"], repo.remote_tip("base"));
+}
+
+#[test]
+fn pull_ff_only_reports_diverged_branches() {
+    let repo = reviewed("fix.txt", "fix\n");
+    commit_on_base(&repo, "local.txt", "local\n");
+    let ours = repo.git(&repo.work, &["rev-parse", "base"]);
+
+    let output = repo.zyra(&["pull", "--ff-only"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'base' changed both locally and on origin."));
+    assert_eq!(repo.git(&repo.work, &["rev-parse", "base"]), ours);
+}
+
+#[test]
+fn pull_marks_conflicting_branches() {
+    let repo = reviewed("a.txt", "theirs\n");
+    commit_on_base(&repo, "a.txt", "ours\n");
+    let ours = repo.git(&repo.work, &["rev-parse", "base"]);
+
+    let output = repo.zyra(&["pull"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("and the changes conflict"));
+    assert_eq!(repo.git(&repo.work, &["rev-parse", "base"]), ours);
+    assert_eq!(repo.branch("base").unwrap()["status"], "Conflict");
+    assert!(!repo.work.join(".git/rebase-merge").exists());
+    assert_eq!(repo.git(&repo.work, &["branch", "--show-current"]), "top");
+}
+
+#[test]
+fn pull_marks_branches_merged_into_the_base() {
+    let repo = Repo::new();
+    assert!(repo.zyra(&["init", "base"]).status.success());
+    repo.commit(&repo.work, "a.txt", "a\n");
+    assert!(repo.zyra(&["push"]).status.success());
+
+    let other = repo.clone_on("main");
+    repo.git(&other, &["merge", "--quiet", "--no-ff", "-m", "Merge base", "origin/base"]);
+    repo.git(&other, &["push", "--quiet", "origin", "main"]);
+
+    let output = repo.zyra(&["pull"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(repo.branch("base").unwrap()["status"], "Merged");
+}
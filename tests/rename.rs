@@ -0,0 +1,66 @@
+mod common;
+
+use common::{MockForge, Repo};
+
+/// A pushed stack `s` -> `a` -> `b`, checked out on `b`.
+fn stack() -> Repo {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("s.txt", "s\n");
+    for name in ["a", "b"] {
+        repo.zyra_ok(&["branch", name]);
+        repo.work_commit(&format!("{}.txt", name), &format!("{}\n", name));
+    }
+    repo.zyra_ok(&["push"]);
+    repo
+}
+
+#[test]
+fn rename_moves_the_branch_everywhere() {
+    let repo = stack();
+    let tip = repo.rev("a");
+
+    repo.zyra_ok(&["rename", "a", "api"]);
+
+    assert_eq!(repo.rev("api"), tip);
+    assert!(repo.work_git(&["branch", "--list", "a"]).is_empty());
+    assert!(repo.branch("a").is_none());
+    assert_eq!(repo.branch("api").unwrap()["parent"], "s");
+    assert_eq!(repo.branch("b").unwrap()["parent"], "api");
+    assert_eq!(repo.remote_tip("api"), tip);
+    assert!(repo.git(&repo.remote, &["branch", "--list", "a"]).is_empty());
+    assert_eq!(repo.work_git(&["rev-parse", "--abbrev-ref", "api@{upstream}"]), "origin/api");
+
+    // Renaming the root renames the stack's head branch too.
+    repo.zyra_ok(&["rename", "s", "root"]);
+    assert_eq!(repo.metadata()["stacks"][0]["head_branch"]["name"], "root");
+    assert_eq!(repo.branch("api").unwrap()["parent"], "root");
+}
+
+#[test]
+fn rename_reopens_its_pull_request_and_retargets_children() {
+    let repo = stack();
+    let forge = MockForge::start();
+    forge.configure(&repo);
+    repo.set_pr("a", 7);
+    repo.set_pr("b", 8);
+
+    let output = repo.zyra_with(&["rename", "a", "api"], &MockForge::env());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let requests = forge.requests();
+    let position = |method: &str, path: &str| {
+        requests
+            .iter()
+            .position(|r| r.method == method && r.path.ends_with(path))
+            .unwrap_or_else(|| panic!("no {} {} in {:?}", method, path, requests))
+    };
+    let retarget = position("PATCH", "/pulls/8");
+    assert_eq!(requests[retarget].body["base"], "api");
+    let open = position("POST", "/pulls");
+    assert_eq!(requests[open].body["head"], "api");
+    assert_eq!(requests[open].body["base"], "s");
+    assert_eq!(requests[open].body["title"], "PR 7");
+    assert_eq!(requests[position("PATCH", "/pulls/7")].body["state"], "closed");
+    assert_eq!(repo.branch("api").unwrap()["pr_id"], 100);
+}
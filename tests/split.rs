@@ -0,0 +1,29 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn next_and_prev_visit_split_parts_in_order() {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("one.txt", "one\n");
+    let first = repo.rev("HEAD");
+    repo.work_commit("two.txt", "two\n");
+    repo.zyra_ok(&["branch", "top"]);
+    repo.work_commit("top.txt", "top\n");
+    repo.zyra_ok(&["goto", "s"]);
+
+    repo.zyra_ok(&["split", "--at", &first, "--name", "s-2"]);
+    let current = || repo.work_git(&["branch", "--show-current"]);
+    assert_eq!(current(), "s-2");
+    assert_eq!(repo.branch("top").unwrap()["parent"], "s-2");
+
+    repo.zyra_ok(&["prev"]);
+    assert_eq!(current(), "s");
+    repo.zyra_ok(&["next"]);
+    assert_eq!(current(), "s-2");
+    repo.zyra_ok(&["next"]);
+    assert_eq!(current(), "top");
+    repo.zyra_ok(&["prev"]);
+    assert_eq!(current(), "s-2");
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::Repo;
+
+fn stack() -> Repo {
+    let repo = Repo::new();
+    repo.zyra_ok(&["init", "s", "--base", "main"]);
+    repo.work_commit("s.txt", "s\n");
+    repo
+}
+
+fn stage(repo: &Repo, file: &str) {
+    std::fs::write(repo.work.join(file), "staged\n").unwrap();
+    repo.work_git(&["add", file]);
+}
+
+#[test]
+fn undo_and_redo_create() {
+    let repo = stack();
+    stage(&repo, "feat.txt");
+    repo.zyra_ok(&["create", "feat", "-m", "feat"]);
+    let tip = repo.rev("feat");
+
+    repo.zyra_ok(&["undo"]);
+    assert!(repo.work_git(&["branch", "--list", "feat"]).is_empty());
+    assert!(repo.branch("feat").is_none());
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "s");
+
+    repo.zyra_ok(&["redo"]);
+    assert_eq!(repo.rev("feat"), tip);
+    assert_eq!(repo.branch("feat").unwrap()["parent"], "s");
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "feat");
+}
+
+#[test]
+fn undo_and_redo_restack() {
+    let repo = stack();
+    repo.zyra_ok(&["branch", "top"]);
+    repo.work_commit("top.txt", "top\n");
+    repo.zyra_ok(&["goto", "s"]);
+    repo.work_commit("more.txt", "more\n");
+    repo.zyra_ok(&["goto", "top"]);
+    let before = repo.rev("top");
+
+    repo.zyra_ok(&["restack"]);
+    let after = repo.rev("top");
+    assert_ne!(before, after);
+
+    repo.zyra_ok(&["undo"]);
+    assert_eq!(repo.rev("top"), before);
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "top");
+
+    repo.zyra_ok(&["redo"]);
+    assert_eq!(repo.rev("top"), after);
+    repo.work_git(&["merge-base", "--is-ancestor", "s", "top"]);
+}
+
+#[test]
+fn undo_refuses_when_a_branch_moved_since() {
+    let repo = stack();
+    stage(&repo, "feat.txt");
+    repo.zyra_ok(&["create", "feat", "-m", "feat"]);
+    repo.work_commit("later.txt", "later\n");
+    let (tip, metadata) = (repo.rev("feat"), repo.metadata());
+
+    let output = repo.zyra(&["undo"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has moved since"));
+    assert_eq!(repo.rev("feat"), tip);
+    assert_eq!(repo.metadata(), metadata);
+    assert_eq!(repo.work_git(&["branch", "--show-current"]), "feat");
+}